@@ -1,4 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion};
+use dhcproto::v4::{DhcpOption, DhcpOptions, OptionCode, UnknownOption};
 use dhcproto::Decodable;
 
 fn decode_benches(c: &mut Criterion) {
@@ -137,6 +138,20 @@ fn decode_benches(c: &mut Criterion) {
         ];
         b.iter(|| dhcproto::v4::DhcpOptions::from_bytes(opts).unwrap());
     });
+
+    g.bench_function("get_opts", |b| {
+        // 30 options, each looked up 100 times
+        let opts = (1..=30)
+            .map(|i| DhcpOption::Unknown(UnknownOption::new(i.into(), vec![i])))
+            .collect::<DhcpOptions>();
+        b.iter(|| {
+            for _ in 0..100 {
+                for i in 1..=30u8 {
+                    criterion::black_box(opts.get(OptionCode::from(i)));
+                }
+            }
+        });
+    });
 }
 
 criterion_group!(benches, decode_benches);
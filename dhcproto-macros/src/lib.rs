@@ -106,9 +106,28 @@ fn generate_optioncode_code<'a>(entries: &'a [Entry]) -> impl Iterator<Item = St
         "OptionCode::Unknown(code) => code }}}".to_owned(),
     ));
 
+    let impl_name = std::iter::once(
+        "
+        impl OptionCode {
+        /// name of the `OptionCode` variant for a known option (i.e. `\"DomainNameServer\"`
+        /// for 6), not the IANA registry name. `None` for `Unknown`
+        pub fn name(&self) -> Option<&'static str> {
+            Some(match self {
+        "
+        .to_owned(),
+    )
+    .chain(entries.iter().map(|e| {
+        let id = &e.id;
+        format!("Self::{id} => \"{id}\",")
+    }))
+    .chain(std::iter::once(
+        "Self::Unknown(_) => return None, })}}".to_owned(),
+    ));
+
     enum_impl
         .chain(impl_option_from_u8)
         .chain(impl_u8_from_option)
+        .chain(impl_name)
 }
 
 fn generate_dhcpoption_code<'a>(entries: &'a [Entry]) -> impl Iterator<Item = String> + 'a {
@@ -0,0 +1,8 @@
+#![no_main]
+use dhcproto::{v4, Decodable};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // must not panic on untrusted input
+    let _ = v4::Message::from_bytes(data);
+});
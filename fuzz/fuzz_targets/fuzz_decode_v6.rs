@@ -0,0 +1,9 @@
+#![no_main]
+use dhcproto::{v6, Decodable};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // must not panic on untrusted input
+    let _ = v6::Message::from_bytes(data);
+    let _ = v6::RelayMessage::from_bytes(data);
+});
@@ -0,0 +1,12 @@
+#![no_main]
+use dhcproto::{v4, Decodable, Encodable};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // anything we can decode should encode & decode back to the same message
+    if let Ok(msg) = v4::Message::from_bytes(data) {
+        let buf = msg.to_vec().expect("failed to encode decoded message");
+        let other = v4::Message::from_bytes(&buf).expect("failed to decode encoded message");
+        assert_eq!(msg, other);
+    }
+});
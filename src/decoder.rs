@@ -12,6 +12,7 @@ use std::{
     ffi::{CStr, CString},
     mem,
     net::{Ipv4Addr, Ipv6Addr},
+    num::{NonZeroU32, NonZeroU8},
     str,
 };
 
@@ -28,7 +29,20 @@ pub trait Decodable: Sized {
 }
 
 /// Decoder type. Wraps a buffer which only contains bytes that have not been read yet
-#[derive(Debug)]
+///
+/// Cloning is cheap, clone before a speculative parse to backtrack if it fails
+///
+/// ```rust
+/// use dhcproto::Decoder;
+///
+/// let mut decoder = Decoder::new(&[0, 1, 2]);
+/// let saved = decoder.clone();
+/// // a u8 then a u32 doesn't fit
+/// assert!(decoder.read_u8().and_then(|_| decoder.read_u32()).is_err());
+/// decoder = saved;
+/// assert_eq!(decoder.read_u16().unwrap(), 1);
+/// ```
+#[derive(Debug, Clone)]
 pub struct Decoder<'a> {
     buffer: &'a [u8],
 }
@@ -49,27 +63,58 @@ impl<'a> Decoder<'a> {
         Ok(u8::from_be_bytes(self.read::<{ mem::size_of::<u8>() }>()?))
     }
 
-    /// read a u32
+    /// read a big endian (network order) u32
     pub fn read_u32(&mut self) -> DecodeResult<u32> {
         Ok(u32::from_be_bytes(
             self.read::<{ mem::size_of::<u32>() }>()?,
         ))
     }
 
-    /// read a i32
+    /// read a u8, returning `UnexpectedZero` if it's 0
+    pub fn read_non_zero_u8(&mut self) -> DecodeResult<NonZeroU8> {
+        NonZeroU8::new(self.read_u8()?).ok_or(DecodeError::UnexpectedZero)
+    }
+
+    /// read a big endian (network order) u32, returning `UnexpectedZero` if it's 0
+    pub fn read_non_zero_u32(&mut self) -> DecodeResult<NonZeroU32> {
+        NonZeroU32::new(self.read_u32()?).ok_or(DecodeError::UnexpectedZero)
+    }
+
+    /// read a big endian (network order) i32
     pub fn read_i32(&mut self) -> DecodeResult<i32> {
         Ok(i32::from_be_bytes(
             self.read::<{ mem::size_of::<i32>() }>()?,
         ))
     }
 
-    /// read a u16
+    /// read a big endian (network order) u16
     pub fn read_u16(&mut self) -> DecodeResult<u16> {
         Ok(u16::from_be_bytes(
             self.read::<{ mem::size_of::<u16>() }>()?,
         ))
     }
 
+    /// read a little endian u16
+    pub fn read_u16_le(&mut self) -> DecodeResult<u16> {
+        Ok(u16::from_le_bytes(
+            self.read::<{ mem::size_of::<u16>() }>()?,
+        ))
+    }
+
+    /// read a little endian u32
+    pub fn read_u32_le(&mut self) -> DecodeResult<u32> {
+        Ok(u32::from_le_bytes(
+            self.read::<{ mem::size_of::<u32>() }>()?,
+        ))
+    }
+
+    /// read a little endian i32
+    pub fn read_i32_le(&mut self) -> DecodeResult<i32> {
+        Ok(i32::from_le_bytes(
+            self.read::<{ mem::size_of::<i32>() }>()?,
+        ))
+    }
+
     /// read a u64
     pub fn read_u64(&mut self) -> DecodeResult<u64> {
         Ok(u64::from_be_bytes(
@@ -97,26 +142,28 @@ impl<'a> Decoder<'a> {
         Ok(self.buffer[..N].try_into().unwrap())
     }
 
-    /// read a `MAX` length bytes into nul terminated `CString`
+    /// read a `MAX` length bytes into nul terminated `CString`. If there is
+    /// no nul all `MAX` bytes are used.
     pub fn read_cstring<const MAX: usize>(&mut self) -> DecodeResult<Option<CString>> {
         let bytes = self.read::<MAX>()?;
         let nul_idx = bytes.iter().position(|&b| b == 0);
         match nul_idx {
             Some(n) if n == 0 => Ok(None),
             Some(n) => Ok(Some(CStr::from_bytes_with_nul(&bytes[..=n])?.to_owned())),
-            // TODO: error?
-            None => Ok(None),
+            // field filled without a terminator, can't contain a nul
+            None => Ok(Some(CString::new(bytes.to_vec()).expect("no nul in bytes"))),
         }
     }
 
+    /// read `MAX` length bytes up to & including the first nul. If there is
+    /// no nul all `MAX` bytes are returned.
     pub fn read_nul_bytes<const MAX: usize>(&mut self) -> DecodeResult<Option<Vec<u8>>> {
         let bytes = self.read::<MAX>()?;
         let nul_idx = bytes.iter().position(|&b| b == 0);
         match nul_idx {
             Some(n) if n == 0 => Ok(None),
             Some(n) => Ok(Some(bytes[..=n].to_vec())),
-            // TODO: error?
-            None => Ok(None),
+            None => Ok(Some(bytes.to_vec())),
         }
     }
 
@@ -128,6 +175,17 @@ impl<'a> Decoder<'a> {
             .transpose()?)
     }
 
+    /// read `len` bytes as a list of `T`, decoding until all `len` bytes
+    /// are used. An error from `T::decode` is returned as-is.
+    pub fn read_list<T: Decodable>(&mut self, len: usize) -> DecodeResult<Vec<T>> {
+        let mut decoder = Decoder::new(self.read_slice(len)?);
+        let mut list = Vec::new();
+        while !decoder.buffer.is_empty() {
+            list.push(T::decode(&mut decoder)?);
+        }
+        Ok(list)
+    }
+
     /// read a slice of bytes determined at runtime
     pub fn read_slice(&mut self, len: usize) -> DecodeResult<&'a [u8]> {
         if len > self.buffer.len() {
@@ -138,6 +196,11 @@ impl<'a> Decoder<'a> {
         Ok(slice)
     }
 
+    /// peek at the next `len` bytes without advancing the internal pointer
+    pub fn peek_slice(&self, len: usize) -> DecodeResult<&'a [u8]> {
+        self.buffer.get(..len).ok_or(DecodeError::NotEnoughBytes)
+    }
+
     /// Read a utf-8 encoded String
     pub fn read_string(&mut self, len: usize) -> DecodeResult<String> {
         Ok(self.read_str(len)?.to_owned())
@@ -170,6 +233,15 @@ impl<'a> Decoder<'a> {
             .collect())
     }
 
+    /// Read an ipv6 addr
+    pub fn read_ipv6(&mut self, length: usize) -> DecodeResult<Ipv6Addr> {
+        if length != 16 {
+            return Err(DecodeError::NotEnoughBytes);
+        }
+        let bytes = self.read::<16>()?;
+        Ok(bytes.into())
+    }
+
     /// Read a list of ipv6 addrs
     pub fn read_ipv6s(&mut self, length: usize) -> DecodeResult<Vec<Ipv6Addr>> {
         // must be multiple of 16
@@ -222,3 +294,97 @@ impl<'a> Decoder<'a> {
         self.buffer
     }
 }
+
+/// returns an error if `s` isn't a url with one of `schemes`
+pub(crate) fn check_url_scheme(s: &str, schemes: &[&str]) -> DecodeResult<()> {
+    let url = url::Url::parse(s)?;
+    if !schemes.contains(&url.scheme()) {
+        return Err(DecodeError::InvalidUrlScheme(url.scheme().to_owned()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2 byte big endian pair
+    #[derive(Debug, PartialEq, Eq)]
+    struct Pair(u8, u8);
+
+    impl Decodable for Pair {
+        fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+            Ok(Pair(decoder.read_u8()?, decoder.read_u8()?))
+        }
+    }
+
+    #[test]
+    fn peek_slice() -> DecodeResult<()> {
+        let mut decoder = Decoder::new(&[1, 2, 3]);
+        assert_eq!(decoder.peek_slice(2)?, [1, 2]);
+        assert_eq!(decoder.peek_slice(0)?, []);
+        assert!(decoder.peek_slice(4).is_err());
+        assert_eq!(decoder.read_slice(3)?, [1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn byte_order() -> DecodeResult<()> {
+        let buf = [1, 2, 3, 4, 0xff, 0xff, 0xff, 0xfe];
+        assert_eq!(Decoder::new(&buf).read_u16()?, 0x0102);
+        assert_eq!(Decoder::new(&buf).read_u16_le()?, 0x0201);
+        let mut decoder = Decoder::new(&buf);
+        assert_eq!(decoder.read_u32_le()?, 0x0403_0201);
+        assert_eq!(decoder.read_i32_le()?, -16_777_217);
+        assert!(decoder.read_u16_le().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn read_non_zero() -> DecodeResult<()> {
+        let mut decoder = Decoder::new(&[1, 0, 0, 0, 2, 0]);
+        assert_eq!(decoder.read_non_zero_u8()?.get(), 1);
+        assert_eq!(decoder.read_non_zero_u32()?.get(), 2);
+        assert!(matches!(
+            decoder.read_non_zero_u8(),
+            Err(DecodeError::UnexpectedZero)
+        ));
+        assert!(matches!(
+            Decoder::new(&[0; 4]).read_non_zero_u32(),
+            Err(DecodeError::UnexpectedZero)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn read_ipv6() -> DecodeResult<()> {
+        let addr = "2001:db8::1".parse::<Ipv6Addr>().unwrap();
+        let mut buf = addr.octets().to_vec();
+        buf.push(9);
+        let mut decoder = Decoder::new(&buf);
+        assert_eq!(decoder.read_ipv6(16)?, addr);
+        assert_eq!(decoder.buffer(), [9]);
+        assert!(Decoder::new(&buf).read_ipv6(17).is_err());
+        assert!(Decoder::new(&buf[..8]).read_ipv6(16).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn read_list() -> DecodeResult<()> {
+        let mut decoder = Decoder::new(&[1, 2, 3, 4, 5]);
+        assert_eq!(decoder.read_list::<Pair>(4)?, [Pair(1, 2), Pair(3, 4)]);
+        // only `len` bytes are consumed
+        assert_eq!(decoder.buffer(), [5]);
+        assert!(decoder.read_list::<Pair>(0)?.is_empty());
+
+        // last item is cut off
+        let mut decoder = Decoder::new(&[1, 2, 3]);
+        assert!(matches!(
+            decoder.read_list::<Pair>(3),
+            Err(DecodeError::NotEnoughBytes)
+        ));
+        // len longer than the buffer
+        assert!(Decoder::new(&[1, 2]).read_list::<Pair>(4).is_err());
+        Ok(())
+    }
+}
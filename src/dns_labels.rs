@@ -0,0 +1,166 @@
+//! Uncompressed DNS name encoding
+//! <https://www.rfc-editor.org/rfc/rfc1035#section-3.1>
+//!
+//! A name is encoded as a sequence of length-prefixed labels ending in the
+//! zero-length root label, i.e. `example.com` is `\x07example\x03com\x00`.
+//! Labels are limited to letters, digits & hyphen
+//! ([RFC 1123](https://www.rfc-editor.org/rfc/rfc1123#section-2.1) allows a
+//! leading digit) and compression pointers are rejected.
+//!
+//! Options that allow compression, like domain search (119), keep using
+//! [`Name`](crate::Name).
+//!
+//! ```rust
+//! use dhcproto::dns_labels;
+//!
+//! let buf = dns_labels::encode("example.com").unwrap();
+//! assert_eq!(buf, b"\x07example\x03com\x00");
+//! assert_eq!(dns_labels::decode(&buf).unwrap(), ["example", "com"]);
+//! ```
+use crate::error::DnsLabelError;
+
+/// max length of a single label
+pub const MAX_LABEL_LEN: usize = 63;
+/// max length of an encoded name, including length bytes & root label
+pub const MAX_NAME_LEN: usize = 255;
+
+/// Encode a dotted name as labels. A trailing `.` is optional, `""` and
+/// `"."` encode the root name.
+pub fn encode(name: &str) -> Result<Vec<u8>, DnsLabelError> {
+    let name = name.strip_suffix('.').unwrap_or(name);
+    let mut buf = Vec::with_capacity(name.len() + 2);
+    if !name.is_empty() {
+        for label in name.split('.') {
+            validate(label.as_bytes())?;
+            buf.push(label.len() as u8);
+            buf.extend_from_slice(label.as_bytes());
+        }
+    }
+    buf.push(0);
+    if buf.len() > MAX_NAME_LEN {
+        return Err(DnsLabelError::NameTooLong { len: buf.len() });
+    }
+    Ok(buf)
+}
+
+/// Check a dotted name against the label rules without encoding it, a
+/// trailing `.` is optional.
+///
+/// Text options like hostname (12) & domain name (15) are decoded as any
+/// utf-8 string since clients commonly send names with `_` or other
+/// characters DNS labels don't allow, use this to enforce
+/// [RFC 952](https://www.rfc-editor.org/rfc/rfc952) style names.
+///
+/// ```rust
+/// use dhcproto::{dns_labels, error::DnsLabelError};
+///
+/// assert!(dns_labels::validate_name("host-1").is_ok());
+/// assert!(dns_labels::validate_name("example.com.").is_ok());
+/// assert_eq!(
+///     dns_labels::validate_name("DESKTOP_1"),
+///     Err(DnsLabelError::InvalidLabel("DESKTOP_1".to_owned()))
+/// );
+/// ```
+pub fn validate_name(name: &str) -> Result<(), DnsLabelError> {
+    encode(name).map(|_| ())
+}
+
+/// Decode a single name, returning its labels without the root label.
+/// `bytes` must contain exactly one name.
+pub fn decode(bytes: &[u8]) -> Result<Vec<String>, DnsLabelError> {
+    let mut labels = Vec::new();
+    let mut offset = 0;
+    loop {
+        let len = *bytes.get(offset).ok_or(DnsLabelError::MissingRoot)?;
+        match len & 0xC0 {
+            0x00 => {}
+            0xC0 => return Err(DnsLabelError::Compressed { offset }),
+            _ => return Err(DnsLabelError::ReservedLabelType(len)),
+        }
+        offset += 1;
+        if len == 0 {
+            break;
+        }
+        let label = bytes
+            .get(offset..offset + len as usize)
+            .ok_or(DnsLabelError::MissingRoot)?;
+        validate(label)?;
+        // checked against the max before the root label is read
+        if offset + label.len() + 1 > MAX_NAME_LEN {
+            return Err(DnsLabelError::NameTooLong {
+                len: offset + label.len() + 1,
+            });
+        }
+        // validated as ascii
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        offset += label.len();
+    }
+    match bytes.len() - offset {
+        0 => Ok(labels),
+        n => Err(DnsLabelError::TrailingBytes(n)),
+    }
+}
+
+fn validate(label: &[u8]) -> Result<(), DnsLabelError> {
+    match label {
+        [] => Err(DnsLabelError::EmptyLabel),
+        _ if label.len() > MAX_LABEL_LEN => Err(DnsLabelError::LabelTooLong { len: label.len() }),
+        [b'-', ..] | [.., b'-'] => Err(invalid(label)),
+        _ if !label
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || *b == b'-') =>
+        {
+            Err(invalid(label))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn invalid(label: &[u8]) -> DnsLabelError {
+    DnsLabelError::InvalidLabel(String::from_utf8_lossy(label).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("").unwrap(), [0]);
+        assert_eq!(encode(".").unwrap(), [0]);
+        assert_eq!(encode("a.b.").unwrap(), b"\x01a\x01b\x00");
+        assert_eq!(encode("a..b"), Err(DnsLabelError::EmptyLabel));
+        assert_eq!(
+            encode("-a.b"),
+            Err(DnsLabelError::InvalidLabel("-a".to_owned()))
+        );
+        assert_eq!(
+            encode("a_b"),
+            Err(DnsLabelError::InvalidLabel("a_b".to_owned()))
+        );
+        assert_eq!(
+            encode(&"a".repeat(64)),
+            Err(DnsLabelError::LabelTooLong { len: 64 })
+        );
+        let long = vec!["a".repeat(63); 4].join(".");
+        assert_eq!(encode(&long), Err(DnsLabelError::NameTooLong { len: 257 }));
+    }
+
+    #[test]
+    fn test_decode() {
+        assert!(decode(&[0]).unwrap().is_empty());
+        assert_eq!(decode(b"\x011\x03www\x00").unwrap(), ["1", "www"]);
+        assert_eq!(
+            decode(b"\x03www\xC0\x0C"),
+            Err(DnsLabelError::Compressed { offset: 4 })
+        );
+        assert_eq!(decode(b"\x40"), Err(DnsLabelError::ReservedLabelType(0x40)));
+        assert_eq!(decode(b"\x03www"), Err(DnsLabelError::MissingRoot));
+        assert_eq!(decode(b"\x03ww"), Err(DnsLabelError::MissingRoot));
+        assert_eq!(decode(b"\x00\x00"), Err(DnsLabelError::TrailingBytes(1)));
+
+        let name = vec!["a".repeat(63); 3].join(".");
+        let buf = encode(&name).unwrap();
+        assert_eq!(decode(&buf).unwrap().join("."), name);
+    }
+}
@@ -0,0 +1,262 @@
+//! 32-bit durations used by DHCP options
+use std::{fmt, time::Duration};
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    encoder::{Encodable, Encoder},
+    error::{DecodeResult, EncodeResult, RenewalTimeError},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A duration in seconds, encoded as a `u32` on the wire.
+///
+/// Used by lease times, T1/T2 and the v6 preferred/valid lifetimes.
+/// A value of `u32::MAX` means "infinity"
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-7.7>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DhcpDuration(u32);
+
+impl DhcpDuration {
+    /// infinite duration (`0xFFFF_FFFF`)
+    pub const INFINITY: DhcpDuration = DhcpDuration(u32::MAX);
+
+    /// Create a new duration from seconds
+    pub const fn from_secs(secs: u32) -> Self {
+        Self(secs)
+    }
+
+    /// get the duration in seconds
+    pub const fn as_secs(&self) -> u32 {
+        self.0
+    }
+
+    /// returns true if this duration is `INFINITY`
+    pub const fn is_infinite(&self) -> bool {
+        self.0 == u32::MAX
+    }
+
+    /// add two durations, `INFINITY` if either is infinite or the sum is
+    /// too large to represent
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    /// subtract `rhs`, stopping at zero. An infinite duration stays infinite
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        if self.is_infinite() {
+            self
+        } else {
+            Self(self.0.saturating_sub(rhs.0))
+        }
+    }
+
+    /// add two durations, `INFINITY` if either is infinite. Returns `None`
+    /// if a finite sum would reach `INFINITY`
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        if self.is_infinite() || rhs.is_infinite() {
+            return Some(Self::INFINITY);
+        }
+        match self.0.checked_add(rhs.0) {
+            Some(secs) if secs != u32::MAX => Some(Self(secs)),
+            _ => None,
+        }
+    }
+}
+
+impl From<u32> for DhcpDuration {
+    fn from(secs: u32) -> Self {
+        Self(secs)
+    }
+}
+
+impl From<DhcpDuration> for u32 {
+    fn from(d: DhcpDuration) -> Self {
+        d.0
+    }
+}
+
+impl From<Duration> for DhcpDuration {
+    /// converts a `Duration`, saturating at `INFINITY`
+    fn from(d: Duration) -> Self {
+        Self(u32::try_from(d.as_secs()).unwrap_or(u32::MAX))
+    }
+}
+
+impl From<DhcpDuration> for Duration {
+    fn from(d: DhcpDuration) -> Self {
+        Duration::from_secs(d.0 as u64)
+    }
+}
+
+impl fmt::Display for DhcpDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_infinite() {
+            write!(f, "infinite")
+        } else {
+            write!(f, "{}s", self.0)
+        }
+    }
+}
+
+/// Returns the renewal (T1) and rebinding (T2) times for a lease.
+///
+/// When the server doesn't send options 58/59, T1 defaults to 0.5 and T2 to
+/// 0.875 of the lease time
+/// <https://www.rfc-editor.org/rfc/rfc2131#section-4.4.5>. An infinite lease
+/// has infinite defaults.
+///
+/// Server provided values must satisfy `T1 < T2 < lease`, otherwise a
+/// [`RenewalTimeError`] is returned. A time may equal a later one only when
+/// both are infinite.
+///
+/// ```rust
+/// use dhcproto::{calculate_t1_t2, DhcpDuration};
+///
+/// let lease = DhcpDuration::from_secs(3600);
+/// let (t1, t2) = calculate_t1_t2(lease, None, None).unwrap();
+/// assert_eq!(t1, DhcpDuration::from_secs(1800));
+/// assert_eq!(t2, DhcpDuration::from_secs(3150));
+///
+/// // T2 after the lease expires
+/// assert!(calculate_t1_t2(lease, None, Some(DhcpDuration::from_secs(7200))).is_err());
+/// ```
+pub fn calculate_t1_t2(
+    lease: DhcpDuration,
+    t1: Option<DhcpDuration>,
+    t2: Option<DhcpDuration>,
+) -> Result<(DhcpDuration, DhcpDuration), RenewalTimeError> {
+    let default = |num: u64, den: u64| {
+        if lease.is_infinite() {
+            DhcpDuration::INFINITY
+        } else {
+            DhcpDuration((lease.0 as u64 * num / den) as u32)
+        }
+    };
+    // defaults are always in order, only check what the server sent
+    let provided = t1.is_some() || t2.is_some();
+    let t1 = t1.unwrap_or_else(|| default(1, 2));
+    let t2 = t2.unwrap_or_else(|| default(7, 8));
+    if provided {
+        let before =
+            |a: DhcpDuration, b: DhcpDuration| a < b || (a.is_infinite() && b.is_infinite());
+        if !before(t1, t2) {
+            return Err(RenewalTimeError::RenewalAfterRebinding { t1, t2 });
+        }
+        if !before(t2, lease) {
+            return Err(RenewalTimeError::RebindingAfterLease { t2, lease });
+        }
+    }
+    Ok((t1, t2))
+}
+
+impl Decodable for DhcpDuration {
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(decoder.read_u32()?.into())
+    }
+}
+
+impl Encodable for DhcpDuration {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        e.write_u32(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_conversions() {
+        let d = DhcpDuration::from(Duration::from_secs(3600));
+        assert_eq!(d.as_secs(), 3600);
+        assert_eq!(Duration::from(d), Duration::from_secs(3600));
+        // saturates
+        let d = DhcpDuration::from(Duration::from_secs(u64::MAX));
+        assert_eq!(d, DhcpDuration::INFINITY);
+        assert!(d.is_infinite());
+    }
+
+    #[test]
+    fn test_duration_arithmetic() {
+        let secs = DhcpDuration::from_secs;
+        assert!(DhcpDuration::INFINITY > secs(u32::MAX - 1));
+        assert_eq!(secs(10).saturating_add(secs(5)), secs(15));
+        assert_eq!(
+            secs(u32::MAX - 1).saturating_add(secs(5)),
+            DhcpDuration::INFINITY
+        );
+        assert_eq!(
+            DhcpDuration::INFINITY.saturating_add(secs(1)),
+            DhcpDuration::INFINITY
+        );
+        assert_eq!(secs(10).saturating_sub(secs(15)), secs(0));
+        assert_eq!(secs(10).saturating_sub(DhcpDuration::INFINITY), secs(0));
+        assert_eq!(
+            DhcpDuration::INFINITY.saturating_sub(secs(15)),
+            DhcpDuration::INFINITY
+        );
+        assert_eq!(secs(10).checked_add(secs(5)), Some(secs(15)));
+        assert_eq!(
+            DhcpDuration::INFINITY.checked_add(secs(1)),
+            Some(DhcpDuration::INFINITY)
+        );
+        assert_eq!(secs(u32::MAX - 1).checked_add(secs(1)), None);
+        assert_eq!(secs(u32::MAX - 1).checked_add(secs(2)), None);
+    }
+
+    #[test]
+    fn test_duration_display() {
+        assert_eq!(DhcpDuration::from_secs(60).to_string(), "60s");
+        assert_eq!(DhcpDuration::INFINITY.to_string(), "infinite");
+    }
+
+    #[test]
+    fn test_calculate_t1_t2() {
+        let lease = DhcpDuration::from_secs(1000);
+        let secs = DhcpDuration::from_secs;
+        assert_eq!(
+            calculate_t1_t2(lease, None, None),
+            Ok((secs(500), secs(875)))
+        );
+        assert_eq!(
+            calculate_t1_t2(lease, Some(secs(100)), None),
+            Ok((secs(100), secs(875)))
+        );
+        assert_eq!(
+            calculate_t1_t2(lease, Some(secs(900)), None),
+            Err(RenewalTimeError::RenewalAfterRebinding {
+                t1: secs(900),
+                t2: secs(875)
+            })
+        );
+        assert_eq!(
+            calculate_t1_t2(lease, None, Some(lease)),
+            Err(RenewalTimeError::RebindingAfterLease { t2: lease, lease })
+        );
+        // no overflow on large leases
+        assert_eq!(
+            calculate_t1_t2(secs(u32::MAX - 1), None, None).unwrap().1,
+            secs(((u32::MAX - 1) as u64 * 7 / 8) as u32)
+        );
+        let inf = DhcpDuration::INFINITY;
+        assert_eq!(calculate_t1_t2(inf, None, None), Ok((inf, inf)));
+        assert_eq!(
+            calculate_t1_t2(inf, Some(secs(100)), None),
+            Ok((secs(100), inf))
+        );
+    }
+
+    #[test]
+    fn test_duration_roundtrip() -> crate::error::DecodeResult<()> {
+        let buf = DhcpDuration::INFINITY.to_vec().unwrap();
+        assert_eq!(buf, [0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(
+            DhcpDuration::decode(&mut Decoder::new(&buf))?,
+            DhcpDuration::INFINITY
+        );
+        Ok(())
+    }
+}
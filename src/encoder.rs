@@ -1,4 +1,6 @@
 //! Encodable trait & Encoder
+use std::net::Ipv4Addr;
+
 use crate::error::{EncodeError, EncodeResult};
 
 /// A trait for types which are deserializable to DHCP binary formats
@@ -22,12 +24,70 @@ pub trait Encodable {
 pub struct Encoder<'a> {
     buffer: &'a mut Vec<u8>,
     offset: usize,
+    options_limit: Option<usize>,
+    max_size: Option<usize>,
 }
 
 impl<'a> Encoder<'a> {
     /// Create a new Encoder from a mutable buffer
     pub fn new(buffer: &'a mut Vec<u8>) -> Self {
-        Self { buffer, offset: 0 }
+        Self {
+            buffer,
+            offset: 0,
+            options_limit: None,
+            max_size: None,
+        }
+    }
+
+    /// Create a new Encoder that won't write more than `limit` bytes,
+    /// returning [`EncodeError::MessageTooLarge`] instead. A [`v4::Message`]
+    /// is checked before any of it is written, so the buffer never holds a
+    /// partial message.
+    ///
+    /// Servers use this to honour a client's maximum message size (option 57).
+    ///
+    /// [`v4::Message`]: crate::v4::Message
+    pub fn with_max_size(buffer: &'a mut Vec<u8>, limit: usize) -> Self {
+        let mut e = Self::new(buffer);
+        e.max_size = Some(limit);
+        e
+    }
+
+    /// Get the max size, see [`Encoder::with_max_size`]
+    pub fn max_size(&self) -> Option<usize> {
+        self.max_size
+    }
+
+    /// number of bytes written so far
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    // returns an error if writing `additional` bytes would exceed `max_size`
+    fn check_size(&self, additional: usize) -> EncodeResult<()> {
+        match self.max_size {
+            Some(limit) if self.offset + additional > limit => {
+                Err(EncodeError::MessageTooLarge { limit })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Limit the length of a DHCPv4 message's options field, including the
+    /// `End` option. Encoding a [`v4::Message`] with longer options returns
+    /// [`EncodeError::OptionsTooLong`]. Use [`v4::MAX_OPTIONS_LEN`] for the
+    /// size every client must accept. `None` (the default) is unlimited.
+    ///
+    /// [`v4::Message`]: crate::v4::Message
+    /// [`v4::MAX_OPTIONS_LEN`]: crate::v4::MAX_OPTIONS_LEN
+    pub fn set_options_limit(&mut self, limit: Option<usize>) -> &mut Self {
+        self.options_limit = limit;
+        self
+    }
+
+    /// Get the options length limit, see [`Encoder::set_options_limit`]
+    pub fn options_limit(&self) -> Option<usize> {
+        self.options_limit
     }
 
     /// Get a reference to the underlying buffer
@@ -40,6 +100,7 @@ impl<'a> Encoder<'a> {
     ///     number of bytes written
     pub fn write_slice(&mut self, bytes: &[u8]) -> EncodeResult<()> {
         let additional = bytes.len();
+        self.check_size(additional)?;
         // space already reserved, we may not need this
         if self.offset + additional <= self.buffer.len() {
             // if self.offset == self.buffer.len() indexing can panic
@@ -62,11 +123,24 @@ impl<'a> Encoder<'a> {
         Ok(())
     }
 
+    /// write bytes to buffer, returning `PayloadTooLong` if more than
+    /// `max_len` bytes are given. Nothing is written on error.
+    pub fn write_slice_checked(&mut self, bytes: &[u8], max_len: usize) -> EncodeResult<()> {
+        if bytes.len() > max_len {
+            return Err(EncodeError::PayloadTooLong {
+                actual: bytes.len(),
+                max: max_len,
+            });
+        }
+        self.write_slice(bytes)
+    }
+
     /// Write const number of bytes to buffer
     pub fn write<const N: usize>(&mut self, bytes: [u8; N]) -> EncodeResult<()> {
         // TODO: refactor this and above method?
         // only difference is zip & extend
         let additional = bytes.len();
+        self.check_size(additional)?;
         // space already reserved, we may not need this
         if self.offset + additional <= self.buffer.len() {
             // if self.offset == self.buffer.len() indexing can panic
@@ -88,6 +162,87 @@ impl<'a> Encoder<'a> {
         Ok(())
     }
 
+    /// run `f`, returning the number of bytes it wrote. If `f` returns an
+    /// error everything it wrote is rolled back, the buffer & offset are
+    /// as they were before the call.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use dhcproto::Encoder;
+    ///
+    /// let mut buf = Vec::new();
+    /// let mut e = Encoder::with_max_size(&mut buf, 4);
+    /// assert_eq!(e.tentative(|e| e.write_u16(1))?, 2);
+    /// // the u8 is removed when the u32 doesn't fit
+    /// assert!(e
+    ///     .tentative(|e| {
+    ///         e.write_u8(2)?;
+    ///         e.write_u32(3)
+    ///     })
+    ///     .is_err());
+    /// assert_eq!(e.offset(), 2);
+    /// assert_eq!(buf, [0, 1]);
+    /// # Ok(()) }
+    /// ```
+    pub fn tentative<F>(&mut self, f: F) -> EncodeResult<usize>
+    where
+        F: FnOnce(&mut Self) -> EncodeResult<()>,
+    {
+        let start = self.offset;
+        let len = self.buffer.len();
+        // reserved space after the offset may be overwritten by `f`
+        let tail = self.buffer[start..].to_vec();
+        match f(self) {
+            Ok(()) => Ok(self.offset - start),
+            Err(err) => {
+                self.buffer.truncate(len);
+                self.buffer[start..].copy_from_slice(&tail);
+                self.offset = start;
+                Err(err)
+            }
+        }
+    }
+
+    /// write a DHCPv6 option: 2 byte code, 2 byte length & `payload`
+    pub fn write_v6_option(&mut self, code: u16, payload: &[u8]) -> EncodeResult<()> {
+        let len = u16::try_from(payload.len()).map_err(|_| EncodeError::V6OptionTooLong {
+            code,
+            len: payload.len(),
+        })?;
+        self.write_u16(code)?;
+        self.write_u16(len)?;
+        self.write_slice(payload)
+    }
+
+    /// write a DHCPv6 option whose payload is written by `f`. The length is
+    /// filled in afterwards from the number of bytes `f` wrote, so nested
+    /// options can be encoded without a temporary buffer.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use dhcproto::Encoder;
+    ///
+    /// let mut buf = Vec::new();
+    /// let mut e = Encoder::new(&mut buf);
+    /// e.write_v6_option_with(8, |e| e.write_u16(100))?;
+    /// assert_eq!(buf, [0, 8, 0, 2, 0, 100]);
+    /// # Ok(()) }
+    /// ```
+    pub fn write_v6_option_with<F>(&mut self, code: u16, f: F) -> EncodeResult<()>
+    where
+        F: FnOnce(&mut Self) -> EncodeResult<()>,
+    {
+        self.write_u16(code)?;
+        let len_offset = self.offset;
+        // placeholder, overwritten once the payload is written
+        self.write_u16(0)?;
+        f(self)?;
+        let len = self.offset - len_offset - 2;
+        let len = u16::try_from(len).map_err(|_| EncodeError::V6OptionTooLong { code, len })?;
+        self.buffer[len_offset..len_offset + 2].copy_from_slice(&len.to_be_bytes());
+        Ok(())
+    }
+
     /// write a u8
     pub fn write_u8(&mut self, data: u8) -> EncodeResult<()> {
         self.write(data.to_be_bytes())
@@ -112,6 +267,24 @@ impl<'a> Encoder<'a> {
     pub fn write_i32(&mut self, data: i32) -> EncodeResult<()> {
         self.write(data.to_be_bytes())
     }
+    /// write an ipv4 addr
+    pub fn write_ipv4(&mut self, addr: Ipv4Addr) -> EncodeResult<()> {
+        self.write(addr.octets())
+    }
+    /// write a list of ipv4 addrs, nothing is written if they don't all fit
+    pub fn write_ipv4s(&mut self, addrs: &[Ipv4Addr]) -> EncodeResult<()> {
+        self.check_size(addrs.len() * 4)?;
+        addrs.iter().try_for_each(|addr| self.write_ipv4(*addr))
+    }
+    /// write a list of ipv4 addr pairs, nothing is written if they don't
+    /// all fit
+    pub fn write_pair_ipv4s(&mut self, pairs: &[(Ipv4Addr, Ipv4Addr)]) -> EncodeResult<()> {
+        self.check_size(pairs.len() * 8)?;
+        pairs.iter().try_for_each(|(a, b)| {
+            self.write_ipv4(*a)?;
+            self.write_ipv4(*b)
+        })
+    }
     /// Writes bytes to buffer and pads with 0 bytes up to some fill_len
     ///
     /// Returns
@@ -154,6 +327,15 @@ impl<'a> Encoder<'a> {
     }
 }
 
+/// returns an error if `s` isn't a url with one of `schemes`
+pub(crate) fn check_url_scheme(s: &str, schemes: &[&str]) -> EncodeResult<()> {
+    let url = url::Url::parse(s)?;
+    if !schemes.contains(&url.scheme()) {
+        return Err(EncodeError::InvalidUrlScheme(url.scheme().to_owned()));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -181,4 +363,136 @@ mod tests {
         assert_eq!(enc.offset, 4);
         Ok(())
     }
+
+    #[test]
+    fn write_slice_checked() -> EncodeResult<()> {
+        let mut buf = vec![];
+        let mut enc = Encoder::new(&mut buf);
+        enc.write_slice_checked(&[1; 255], 255)?;
+        assert!(matches!(
+            enc.write_slice_checked(&[1; 256], 255),
+            Err(EncodeError::PayloadTooLong {
+                actual: 256,
+                max: 255
+            })
+        ));
+        assert_eq!(enc.offset, 255);
+        Ok(())
+    }
+
+    #[test]
+    fn write_ipv4s() -> EncodeResult<()> {
+        let ips = [Ipv4Addr::new(192, 168, 0, 1), Ipv4Addr::new(10, 0, 0, 1)];
+        let mut buf = vec![];
+        let mut enc = Encoder::new(&mut buf);
+        enc.write_ipv4s(&ips)?;
+        assert_eq!(
+            crate::Decoder::new(enc.buffer()).read_ipv4s(8).unwrap(),
+            ips
+        );
+
+        let mut buf = vec![];
+        let mut enc = Encoder::with_max_size(&mut buf, 6);
+        enc.write_ipv4(ips[0])?;
+        assert!(enc.write_ipv4s(&ips[..1]).is_err());
+        assert_eq!(buf, [192, 168, 0, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn write_pair_ipv4s() -> EncodeResult<()> {
+        let pairs = [
+            (Ipv4Addr::new(10, 0, 0, 0), Ipv4Addr::new(192, 168, 0, 1)),
+            (Ipv4Addr::new(10, 1, 0, 0), Ipv4Addr::new(192, 168, 0, 2)),
+        ];
+        let mut buf = vec![];
+        let mut enc = Encoder::new(&mut buf);
+        enc.write_pair_ipv4s(&pairs)?;
+        assert_eq!(
+            crate::Decoder::new(enc.buffer())
+                .read_pair_ipv4s(16)
+                .unwrap(),
+            pairs
+        );
+
+        let mut buf = vec![];
+        let mut enc = Encoder::with_max_size(&mut buf, 12);
+        assert!(enc.write_pair_ipv4s(&pairs).is_err());
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn tentative() -> EncodeResult<()> {
+        let mut buf = vec![];
+        let mut enc = Encoder::new(&mut buf);
+        enc.write_u8(1)?;
+        let res = enc.tentative(|e| {
+            e.write_slice(&[2, 3])?;
+            Err(EncodeError::OptionsTooLong { len: 2, limit: 1 })
+        });
+        assert!(matches!(res, Err(EncodeError::OptionsTooLong { .. })));
+        assert_eq!(enc.offset(), 1);
+        assert_eq!(enc.buffer(), [1]);
+        assert_eq!(enc.tentative(|e| e.write_slice(&[2, 3]))?, 2);
+        assert_eq!(enc.buffer(), [1, 2, 3]);
+
+        // overwritten reserved space is restored
+        let mut buf = vec![0, 1, 2, 3];
+        let mut enc = Encoder::new(&mut buf);
+        enc.offset = 2;
+        let res = enc.tentative(|e| {
+            e.write_slice(&[9, 9, 9])?;
+            e.write_v6_option(1, &[0; 65536])
+        });
+        assert!(matches!(res, Err(EncodeError::V6OptionTooLong { .. })));
+        assert_eq!(enc.offset(), 2);
+        assert_eq!(buf, [0, 1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn max_size() -> EncodeResult<()> {
+        let mut buf = vec![];
+        let mut enc = Encoder::with_max_size(&mut buf, 4);
+        enc.write_u16(1)?;
+        assert!(matches!(
+            enc.write_u32(1),
+            Err(EncodeError::MessageTooLarge { limit: 4 })
+        ));
+        // nothing written on error
+        assert_eq!(enc.offset(), 2);
+        enc.write_slice(&[1, 2])?;
+        assert!(enc.write_u8(1).is_err());
+        assert_eq!(buf, [0, 1, 1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn v6_option() -> EncodeResult<()> {
+        let mut buf = vec![];
+        let mut enc = Encoder::new(&mut buf);
+        enc.write_v6_option(14, &[])?;
+        enc.write_v6_option(1, &[1, 2, 3])?;
+        // nested
+        enc.write_v6_option_with(3, |e| {
+            e.write_u32(1)?;
+            e.write_v6_option_with(5, |e| e.write_u8(7))
+        })?;
+        assert_eq!(
+            buf,
+            [0, 14, 0, 0, 0, 1, 0, 3, 1, 2, 3, 0, 3, 0, 9, 0, 0, 0, 1, 0, 5, 0, 1, 7]
+        );
+
+        let mut buf = vec![];
+        let mut enc = Encoder::new(&mut buf);
+        assert!(matches!(
+            enc.write_v6_option_with(1, |e| e.write_slice(&[0; 65536])),
+            Err(EncodeError::V6OptionTooLong {
+                code: 1,
+                len: 65536
+            })
+        ));
+        Ok(())
+    }
 }
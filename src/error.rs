@@ -3,6 +3,8 @@ use std::io;
 
 use thiserror::Error;
 
+use crate::duration::DhcpDuration;
+
 /// Convenience type for decode errors
 pub type DecodeResult<T> = Result<T, DecodeError>;
 
@@ -37,6 +39,34 @@ pub enum DecodeError {
     #[error("url parse error")]
     UrlParseError(#[from] url::ParseError),
 
+    /// option value is outside the range allowed by its RFC
+    #[error("option {code} value {value} is out of range")]
+    ValueOutOfRange {
+        /// option code
+        code: u16,
+        /// decoded value
+        value: u32,
+    },
+
+    /// an option's length is longer than the bytes left in the buffer
+    #[error("option {code} declares {declared_len} bytes but only {available} are available")]
+    TruncatedOption {
+        /// option code
+        code: u8,
+        /// length byte of the option
+        declared_len: usize,
+        /// bytes left after the code & length
+        available: usize,
+    },
+
+    /// a field that must be non-zero was 0
+    #[error("unexpected zero value")]
+    UnexpectedZero,
+
+    /// url scheme isn't allowed for the option
+    #[error("unexpected url scheme {0}")]
+    InvalidUrlScheme(String),
+
     /// domain parse error
     #[error("domain parse error {0}")]
     DomainParseError(#[from] trust_dns_proto::error::ProtoError),
@@ -62,6 +92,74 @@ pub enum EncodeError {
         len: usize,
     },
 
+    /// encoded options exceed the encoder's options limit
+    #[error("options are {len} bytes, exceeds the limit of {limit}")]
+    OptionsTooLong {
+        /// length of encoded options
+        len: usize,
+        /// the encoder's limit
+        limit: usize,
+    },
+
+    /// encoding would exceed the encoder's max size
+    #[error("message exceeds max size of {limit}")]
+    MessageTooLarge {
+        /// the encoder's max size
+        limit: usize,
+    },
+
+    /// DHCPv6 option data doesn't fit in a u16 length
+    #[error("option {code} is {len} bytes, exceeds the max of 65535")]
+    V6OptionTooLong {
+        /// option code
+        code: u16,
+        /// length of data
+        len: usize,
+    },
+
+    /// sub-option data doesn't fit in a u8 length
+    #[error("sub-option {code} is {len} bytes, exceeds the max of 255")]
+    SubOptionTooLong {
+        /// sub-option code
+        code: u8,
+        /// length of data
+        len: usize,
+    },
+
+    /// payload is longer than the field it's written to
+    #[error("payload is {actual} bytes, exceeds the max of {max}")]
+    PayloadTooLong {
+        /// length of data
+        actual: usize,
+        /// max length allowed
+        max: usize,
+    },
+
+    /// option value is outside the range allowed by its RFC, the decoder
+    /// would reject it
+    #[error("option {code} value {value} is out of range")]
+    ValueOutOfRange {
+        /// option code
+        code: u16,
+        /// option value
+        value: u32,
+    },
+
+    /// option needs at least one item, the decoder would reject it
+    #[error("option {code} can't be empty")]
+    EmptyList {
+        /// option code
+        code: u16,
+    },
+
+    /// url parse error, the decoder would reject it
+    #[error("url parse error")]
+    UrlParseError(#[from] url::ParseError),
+
+    /// url scheme isn't allowed for the option, the decoder would reject it
+    #[error("unexpected url scheme {0}")]
+    InvalidUrlScheme(String),
+
     /// io error
     #[error("io error {0}")]
     IoError(#[from] io::Error),
@@ -73,3 +171,169 @@ pub enum EncodeError {
 
 /// Convenience type for encode errors
 pub type EncodeResult<T> = Result<T, EncodeError>;
+
+/// Returned from [`calculate_t1_t2`] when the server provided T1/T2 are
+/// out of order
+///
+/// [`calculate_t1_t2`]: crate::duration::calculate_t1_t2
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RenewalTimeError {
+    /// T1 is not less than T2
+    #[error("renewal time (T1) {t1} must be less than rebinding time (T2) {t2}")]
+    RenewalAfterRebinding {
+        /// renewal time
+        t1: DhcpDuration,
+        /// rebinding time
+        t2: DhcpDuration,
+    },
+
+    /// T2 is not less than the lease time
+    #[error("rebinding time (T2) {t2} must be less than lease time {lease}")]
+    RebindingAfterLease {
+        /// rebinding time
+        t2: DhcpDuration,
+        /// lease time
+        lease: DhcpDuration,
+    },
+}
+
+/// Returned from [`dns_labels`](crate::dns_labels) when a name can't be
+/// encoded or decoded
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DnsLabelError {
+    /// a label is longer than 63 bytes
+    #[error("label exceeds 63 bytes, got {len}")]
+    LabelTooLong {
+        /// length of label
+        len: usize,
+    },
+
+    /// the encoded name is longer than 255 bytes
+    #[error("name exceeds 255 bytes, got {len}")]
+    NameTooLong {
+        /// encoded length of name
+        len: usize,
+    },
+
+    /// empty label, i.e. `foo..bar`
+    #[error("empty label")]
+    EmptyLabel,
+
+    /// label contains characters outside of letters, digits & hyphen, or
+    /// starts/ends with a hyphen
+    #[error("invalid label {0:?}")]
+    InvalidLabel(String),
+
+    /// compression pointers aren't allowed here
+    #[error("compression pointer at offset {offset}")]
+    Compressed {
+        /// offset of the pointer in the buffer
+        offset: usize,
+    },
+
+    /// reserved label type (top bits `01` or `10`)
+    #[error("reserved label type {0:#04x}")]
+    ReservedLabelType(u8),
+
+    /// ran out of bytes before the root label
+    #[error("name is missing the root label")]
+    MissingRoot,
+
+    /// bytes left over after the root label
+    #[error("{0} trailing bytes after root label")]
+    TrailingBytes(usize),
+}
+
+/// Returned from [`v4::util`](crate::v4::util) netmask conversions
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NetmaskError {
+    /// mask bits aren't contiguous, i.e. `255.0.255.0`
+    #[error("netmask {0} is not contiguous")]
+    NonContiguous(std::net::Ipv4Addr),
+
+    /// prefix length greater than 32
+    #[error("prefix length {0} is greater than 32")]
+    InvalidPrefixLen(u8),
+}
+
+/// Returned from [`Message::relay_forward`](crate::v4::Message::relay_forward)
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RelayError {
+    /// incrementing `hops` would overflow
+    #[error("hop count overflow")]
+    HopsOverflow,
+}
+
+/// Returned from [`Message::validate`](crate::v4::Message::validate)
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// `hlen` is larger than the 16 byte `chaddr` field
+    #[error("hlen {0} exceeds chaddr length of 16")]
+    HlenTooLong(u8),
+
+    /// `hlen` doesn't match the length for a known `htype`
+    #[error("hlen {hlen} does not match expected length {expected} for htype {htype:?}")]
+    HlenMismatch {
+        /// hardware type
+        htype: crate::v4::HType,
+        /// hlen in message
+        hlen: u8,
+        /// expected hlen for `htype`
+        expected: u8,
+    },
+
+    /// `chaddr` has non-zero bytes after the first `hlen` bytes
+    #[error("chaddr has non-zero bytes after hlen {0}")]
+    ChaddrNotPadded(u8),
+
+    /// renewal (58) & rebinding (59) times are out of order with the lease
+    /// time (51) <https://www.rfc-editor.org/rfc/rfc2131#section-4.4.5>
+    #[error(transparent)]
+    Timers(#[from] RenewalTimeError),
+
+    /// a DHCPREQUEST from a renewing or rebinding client (`ciaddr` set) has
+    /// a requested IP address (50)
+    /// <https://www.rfc-editor.org/rfc/rfc2131#section-4.3.2>
+    #[error("requested ip address option in a DHCPREQUEST with ciaddr set")]
+    RequestedIpWithCiaddr,
+
+    /// a DHCPOFFER, DHCPACK or DHCPNAK has no server identifier (54)
+    /// <https://www.rfc-editor.org/rfc/rfc2131#section-4.3.1>
+    #[error("{0:?} has no server identifier option")]
+    MissingServerIdentifier(crate::v4::MessageType),
+
+    /// a DHCPOFFER or DHCPACK assigning an address has no lease time (51)
+    /// <https://www.rfc-editor.org/rfc/rfc2131#section-4.3.1>
+    #[error("offered or acknowledged address has no lease time option")]
+    MissingLeaseTime,
+
+    /// subnet mask (1) isn't contiguous
+    #[error(transparent)]
+    Netmask(#[from] NetmaskError),
+
+    /// the first router (3) isn't in the client's subnet
+    #[error("router {0} is outside the client's subnet")]
+    RouterOutsideSubnet(std::net::Ipv4Addr),
+}
+
+/// Returned when building a message with invalid fields or options
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// the option isn't allowed in this message type
+    /// <https://www.rfc-editor.org/rfc/rfc2131#section-4.4.1>
+    #[error("option {code:?} is not allowed in {msg_type:?}")]
+    OptionNotAllowed {
+        /// the option
+        code: crate::v4::OptionCode,
+        /// type of message being built
+        msg_type: crate::v4::MessageType,
+    },
+
+    /// `ciaddr` must be set
+    #[error("ciaddr must be set")]
+    UnspecifiedCiaddr,
+
+    /// can't reply to this message type
+    #[error("can't reply to message type {0:?}")]
+    UnexpectedMessageType(Option<crate::v4::MessageType>),
+}
@@ -82,13 +82,59 @@
 //! #        ]
 //! #    }
 //! ```
+//!
+//! ## I/O
+//!
+//! `dhcproto` doesn't do any I/O. DHCP messages are single UDP datagrams, so
+//! receive the whole datagram with your socket (std, tokio, async-std...) and
+//! decode it from the buffer, there is nothing to gain from decoding while
+//! reading. Likewise, encode into a buffer with [`Encodable::to_vec`] and send
+//! it with one `send_to`.
+//!
+//! ```rust,no_run
+//! use std::net::UdpSocket;
+//! use dhcproto::v4::{Decodable, Encodable, Message};
+//! # fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+//! let socket = UdpSocket::bind(("0.0.0.0", dhcproto::v4::SERVER_PORT))?;
+//! let mut buf = [0; 1500];
+//! let (len, src) = socket.recv_from(&mut buf)?;
+//! let msg = Message::from_bytes(&buf[..len])?;
+//! // ... build a reply
+//! socket.send_to(&msg.to_vec()?, src)?;
+//! # Ok(())
+//! # }
+//! ```
+
+/// Generates a test that encodes `$value`, decodes the result and asserts
+/// it's equal to the original.
+#[cfg(test)]
+macro_rules! roundtrip_test {
+    ($name:ident, $value:expr) => {
+        #[test]
+        fn $name() -> std::result::Result<(), Box<dyn std::error::Error>> {
+            fn roundtrip<T>(value: T) -> std::result::Result<(), Box<dyn std::error::Error>>
+            where
+                T: $crate::Decodable + $crate::Encodable + PartialEq + std::fmt::Debug,
+            {
+                let buf = value.to_vec()?;
+                assert_eq!(T::from_bytes(&buf)?, value);
+                Ok(())
+            }
+            roundtrip($value)
+        }
+    };
+}
 
 pub use decoder::{Decodable, Decoder};
+pub use duration::{calculate_t1_t2, DhcpDuration};
 pub use encoder::{Encodable, Encoder};
 
 pub mod decoder;
+pub mod dns_labels;
+pub mod duration;
 pub mod encoder;
 pub mod error;
+pub mod sub_option;
 pub mod v4;
 pub mod v6;
 
@@ -0,0 +1,273 @@
+//! Raw encoding of option lists nested in another option's payload
+//!
+//! Options like vendor extensions (43) or relay agent information (82)
+//! carry a list of sub-options. [`SubOptionCodec`] splits such a payload
+//! into `(code, data)` pairs without interpreting the data, [`U8Tlv`] is the
+//! DHCPv4 1 byte code & length layout and [`U16Tlv`] the DHCPv6 2 byte one.
+//! [`TlvIter`] & [`TlvIter16`] walk the same layouts without copying.
+//!
+//! ```rust
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use dhcproto::{
+//!     sub_option::{SubOptionCodec, U8Tlv},
+//!     Decoder, Encoder,
+//! };
+//!
+//! // option 43 payload
+//! let payload = [1, 2, 0xaa, 0xbb, 2, 0];
+//! let opts = U8Tlv::decode(&mut Decoder::new(&payload))?;
+//! assert_eq!(opts, [(1, vec![0xaa, 0xbb]), (2, vec![])]);
+//!
+//! let mut buf = Vec::new();
+//! U8Tlv::encode(&[(1, &[0xaa, 0xbb]), (2, &[])], &mut Encoder::new(&mut buf))?;
+//! assert_eq!(buf, payload);
+//! # Ok(()) }
+//! ```
+use crate::{
+    decoder::Decoder,
+    encoder::Encoder,
+    error::{DecodeError, DecodeResult, EncodeError, EncodeResult},
+};
+
+const PAD: u8 = 0;
+const END: u8 = 255;
+
+/// Decode & encode a list of TLV sub-options
+pub trait SubOptionCodec {
+    /// sub-option code type
+    type Code: Copy;
+
+    /// read sub-options until the decoder is empty, a truncated sub-option
+    /// is an error
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Vec<(Self::Code, Vec<u8>)>>;
+
+    /// write each `(code, data)` pair in order
+    fn encode(options: &[(Self::Code, &[u8])], encoder: &mut Encoder<'_>) -> EncodeResult<()>;
+}
+
+/// 1 byte code, 1 byte length, used by DHCPv4
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct U8Tlv;
+
+impl SubOptionCodec for U8Tlv {
+    type Code = u8;
+
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Vec<(u8, Vec<u8>)>> {
+        let mut opts = Vec::new();
+        while !decoder.buffer().is_empty() {
+            let code = decoder.read_u8()?;
+            let len = decoder.read_u8()? as usize;
+            opts.push((code, decoder.read_slice(len)?.to_vec()));
+        }
+        Ok(opts)
+    }
+
+    fn encode(options: &[(u8, &[u8])], encoder: &mut Encoder<'_>) -> EncodeResult<()> {
+        for &(code, data) in options {
+            let len = u8::try_from(data.len()).map_err(|_| EncodeError::SubOptionTooLong {
+                code,
+                len: data.len(),
+            })?;
+            encoder.write_u8(code)?;
+            encoder.write_u8(len)?;
+            encoder.write_slice(data)?;
+        }
+        Ok(())
+    }
+}
+
+/// 2 byte code, 2 byte length, used by DHCPv6
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct U16Tlv;
+
+impl SubOptionCodec for U16Tlv {
+    type Code = u16;
+
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Vec<(u16, Vec<u8>)>> {
+        let buf = decoder.read_slice(decoder.buffer().len())?;
+        TlvIter16::new(buf)
+            .map(|opt| opt.map(|(code, data)| (code, data.to_vec())))
+            .collect()
+    }
+
+    fn encode(options: &[(u16, &[u8])], encoder: &mut Encoder<'_>) -> EncodeResult<()> {
+        options
+            .iter()
+            .try_for_each(|&(code, data)| encoder.write_v6_option(code, data))
+    }
+}
+
+/// Borrowed iterator over 1 byte code & length TLVs, laid out like DHCPv4
+/// options. `Pad` (0) is skipped and iteration stops at `End` (255).
+///
+/// A TLV running past the end of the buffer yields
+/// [`DecodeError::TruncatedOption`] and ends the iteration.
+///
+/// ```rust
+/// use dhcproto::sub_option::TlvIter;
+///
+/// let buf = [1, 2, 0xaa, 0xbb, 0, 2, 0, 255, 3, 0];
+/// let opts = TlvIter::new(&buf).collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(opts, [(1, &[0xaa, 0xbb][..]), (2, &[][..])]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TlvIter<'a> {
+    buf: &'a [u8],
+    done: bool,
+}
+
+impl<'a> TlvIter<'a> {
+    /// iterate over the TLVs in `buf`
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, done: false }
+    }
+}
+
+impl<'a> Iterator for TlvIter<'a> {
+    type Item = DecodeResult<(u8, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let (code, rest) = match self.buf.split_first() {
+                Some((&PAD, rest)) => {
+                    self.buf = rest;
+                    continue;
+                }
+                Some((&END, _)) | None => break,
+                Some((&code, rest)) => (code, rest),
+            };
+            return Some(match rest.split_first() {
+                Some((&len, rest)) if rest.len() >= len as usize => {
+                    let (data, rest) = rest.split_at(len as usize);
+                    self.buf = rest;
+                    Ok((code, data))
+                }
+                Some((&len, rest)) => {
+                    self.done = true;
+                    Err(DecodeError::TruncatedOption {
+                        code,
+                        declared_len: len as usize,
+                        available: rest.len(),
+                    })
+                }
+                None => {
+                    self.done = true;
+                    Err(DecodeError::NotEnoughBytes)
+                }
+            });
+        }
+        self.done = true;
+        None
+    }
+}
+
+/// Borrowed iterator over 2 byte code & length TLVs, laid out like DHCPv6
+/// options. There is no pad or end code, iteration stops when the buffer is
+/// empty.
+///
+/// A truncated TLV yields [`DecodeError::NotEnoughBytes`] and ends the
+/// iteration.
+#[derive(Debug, Clone)]
+pub struct TlvIter16<'a> {
+    buf: &'a [u8],
+    done: bool,
+}
+
+impl<'a> TlvIter16<'a> {
+    /// iterate over the TLVs in `buf`
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, done: false }
+    }
+}
+
+impl<'a> Iterator for TlvIter16<'a> {
+    type Item = DecodeResult<(u16, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.buf.is_empty() {
+            self.done = true;
+            return None;
+        }
+        let opt = match self.buf {
+            [c0, c1, l0, l1, rest @ ..] => {
+                let len = u16::from_be_bytes([*l0, *l1]) as usize;
+                (rest.len() >= len).then(|| (u16::from_be_bytes([*c0, *c1]), rest.split_at(len)))
+            }
+            _ => None,
+        };
+        Some(match opt {
+            Some((code, (data, rest))) => {
+                self.buf = rest;
+                Ok((code, data))
+            }
+            None => {
+                self.done = true;
+                Err(DecodeError::NotEnoughBytes)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn u16_tlv() -> Result<(), Box<dyn std::error::Error>> {
+        // vendor-opts (17) payload after the enterprise number
+        let payload = [0, 1, 0, 3, 1, 2, 3, 0, 2, 0, 0];
+        let opts = U16Tlv::decode(&mut Decoder::new(&payload))?;
+        assert_eq!(opts, [(1, vec![1, 2, 3]), (2, vec![])]);
+
+        let mut buf = Vec::new();
+        U16Tlv::encode(&[(1, &[1, 2, 3]), (2, &[])], &mut Encoder::new(&mut buf))?;
+        assert_eq!(buf, payload);
+        Ok(())
+    }
+
+    #[test]
+    fn truncated() {
+        assert!(U8Tlv::decode(&mut Decoder::new(&[1, 3, 0])).is_err());
+        assert!(U16Tlv::decode(&mut Decoder::new(&[0, 1, 0])).is_err());
+    }
+
+    #[test]
+    fn tlv_iter() {
+        let mut iter = TlvIter::new(&[0, 0, 1, 1, 9, 2, 3, 0]);
+        assert_eq!(iter.next().unwrap().unwrap(), (1, &[9][..]));
+        assert!(matches!(
+            iter.next(),
+            Some(Err(DecodeError::TruncatedOption {
+                code: 2,
+                declared_len: 3,
+                available: 1
+            }))
+        ));
+        assert!(iter.next().is_none());
+        // code without a length
+        let mut iter = TlvIter::new(&[1]);
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+        assert!(TlvIter::new(&[255, 1, 0]).next().is_none());
+    }
+
+    #[test]
+    fn tlv_iter16() {
+        let buf = [0, 1, 0, 1, 9, 0, 0, 0, 0, 0, 2, 0, 2, 1];
+        let mut iter = TlvIter16::new(&buf);
+        assert_eq!(iter.next().unwrap().unwrap(), (1, &[9][..]));
+        // code 0 isn't padding in DHCPv6
+        assert_eq!(iter.next().unwrap().unwrap(), (0, &[][..]));
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn too_long() {
+        let mut buf = Vec::new();
+        assert!(matches!(
+            U8Tlv::encode(&[(9, &[0; 256])], &mut Encoder::new(&mut buf)),
+            Err(EncodeError::SubOptionTooLong { code: 9, len: 256 })
+        ));
+    }
+}
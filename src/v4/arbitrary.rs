@@ -0,0 +1,402 @@
+//! [`proptest`] strategies for DHCPv4 types, enabled with the `proptest` feature
+use std::{fmt, net::Ipv4Addr};
+
+use ipnet::Ipv4Net;
+use proptest::prelude::*;
+use trust_dns_proto::rr::Name;
+
+use crate::v4::{
+    bulk_query, fqdn,
+    relay::{RelayAgentInformation, RelayInfo},
+    DhcpOption, DhcpOptions, Flags, HType, Message, MessageType, Opcode, OptionCode, UnknownOption,
+    MAGIC, MIN_INTERFACE_MTU,
+};
+
+impl Arbitrary for Opcode {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<u8>().prop_map(Opcode::from).boxed()
+    }
+}
+
+impl Arbitrary for HType {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<u8>().prop_map(HType::from).boxed()
+    }
+}
+
+impl Arbitrary for Flags {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<u16>().prop_map(Flags::new).boxed()
+    }
+}
+
+impl Arbitrary for MessageType {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        // only the types defined in RFC 2131
+        (1..=8u8).prop_map(MessageType::from).boxed()
+    }
+}
+
+impl Arbitrary for OptionCode {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<u8>().prop_map(OptionCode::from).boxed()
+    }
+}
+
+impl Arbitrary for RelayAgentInformation {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let info = prop_oneof![
+            prop::collection::vec(any::<u8>(), 1..32).prop_map(RelayInfo::AgentCircuitId),
+            prop::collection::vec(any::<u8>(), 1..32).prop_map(RelayInfo::AgentRemoteId),
+            any::<Ipv4Addr>().prop_map(RelayInfo::LinkSelection),
+            prop::collection::vec(any::<u8>(), 1..32).prop_map(RelayInfo::SubscriberId),
+        ];
+        prop::collection::vec(info, 1..4)
+            .prop_map(|infos| {
+                let mut relay = RelayAgentInformation::default();
+                for info in infos {
+                    relay.insert(info);
+                }
+                relay
+            })
+            .boxed()
+    }
+}
+
+fn ipv4s() -> impl Strategy<Value = Vec<Ipv4Addr>> {
+    prop::collection::vec(any::<Ipv4Addr>(), 1..8)
+}
+
+fn bytes() -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(any::<u8>(), 1..32)
+}
+
+fn label() -> impl Strategy<Value = String> {
+    "[a-z][a-z0-9-]{0,30}"
+}
+
+fn name() -> impl Strategy<Value = Name> {
+    label().prop_map(|l| Name::from_ascii(format!("{l}.example.com.")).unwrap())
+}
+
+// picks one of `variants` & fills it with a value from `value`
+fn one_of<T: Clone + fmt::Debug + 'static>(
+    variants: Vec<fn(T) -> DhcpOption>,
+    value: impl Strategy<Value = T>,
+) -> impl Strategy<Value = DhcpOption> {
+    (prop::sample::select(variants), value).prop_map(|(variant, v)| variant(v))
+}
+
+impl Arbitrary for DhcpOption {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    // every variant except `Pad`/`End`, which are markers `DhcpOptions` handles
+    // itself, and `OptionOverload`, which would make a `Message` read its
+    // sname/file as options
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        use DhcpOption as O;
+        prop_oneof![
+            one_of(
+                vec![
+                    O::Router,
+                    O::TimeServer,
+                    O::NameServer,
+                    O::DomainNameServer,
+                    O::LogServer,
+                    O::QuoteServer,
+                    O::LprServer,
+                    O::ImpressServer,
+                    O::ResourceLocationServer,
+                    O::NisServers,
+                    O::NtpServers,
+                    O::NetBiosNameServers,
+                    O::NetBiosDatagramDistributionServer,
+                    O::XFontServer,
+                    O::XDisplayManager,
+                    O::NispServers,
+                    O::MobileIpHomeAgent,
+                    O::SmtpServer,
+                    O::Pop3Server,
+                    O::NntpServer,
+                    O::WwwServer,
+                    O::DefaultFingerServer,
+                    O::IrcServer,
+                    O::StreetTalkServer,
+                    O::StreetTalkDirectoryAssistance,
+                    O::NdsServers,
+                    O::BcmsControllerAddrs,
+                    O::AssociatedIp,
+                ],
+                ipv4s(),
+            ),
+            one_of(
+                vec![
+                    O::SubnetMask,
+                    O::SwapServer,
+                    O::BroadcastAddr,
+                    O::RouterSolicitationAddr,
+                    O::RequestedIpAddress,
+                    O::ServerIdentifier,
+                    O::SubnetSelection,
+                    O::TFTPServerAddress,
+                ],
+                any::<Ipv4Addr>(),
+            ),
+            one_of(
+                vec![O::PolicyFilter, O::StaticRoutingTable],
+                prop::collection::vec((any::<Ipv4Addr>(), any::<Ipv4Addr>()), 1..8),
+            ),
+            one_of(
+                vec![
+                    O::Hostname,
+                    O::MeritDumpFile,
+                    O::DomainName,
+                    O::RootPath,
+                    O::ExtensionsPath,
+                    O::NisDomain,
+                    O::NetBiosScope,
+                    O::Message,
+                    O::NwipDomainName,
+                    O::NispServiceDomain,
+                    O::NdsTreeName,
+                    O::NdsContext,
+                    O::PCode,
+                    O::TCode,
+                ],
+                label(),
+            ),
+            one_of(
+                vec![
+                    O::VendorExtensions,
+                    O::ClassIdentifier,
+                    O::ClientIdentifier,
+                    O::NwipInformation,
+                    O::TFTPServerName,
+                    O::BootfileName,
+                    O::UserClass,
+                    O::ClientMachineIdentifier,
+                    O::ViVendorClass,
+                    O::ViVendorSpecificInfo,
+                ],
+                bytes(),
+            ),
+            one_of(
+                vec![
+                    O::IpForwarding,
+                    O::NonLocalSrcRouting,
+                    O::AllSubnetsLocal,
+                    O::PerformMaskDiscovery,
+                    O::MaskSupplier,
+                    O::PerformRouterDiscovery,
+                    O::TrailerEncapsulated,
+                    O::EthernetEncapsulation,
+                    O::TcpKeepaliveGarbage,
+                ],
+                any::<bool>(),
+            ),
+            one_of(
+                vec![O::DefaultIpTtl, O::DefaultTcpTtl, O::AutoConfig],
+                any::<u8>()
+            ),
+            one_of(
+                vec![O::BootFileSize, O::MaxDatagramSize, O::MaxMessageSize],
+                any::<u16>(),
+            ),
+            one_of(
+                vec![
+                    O::PathMtuAgingTimeout,
+                    O::ArpCacheTimeout,
+                    O::TcpKeepaliveInterval,
+                    O::AddressLeaseTime,
+                    O::Renewal,
+                    O::Rebinding,
+                    O::ClientLastTransactionTime,
+                    O::BulkLeaseQueryBaseTime,
+                    O::BulkLeasQueryStartTimeOfState,
+                    O::BulkLeaseQueryQueryStartTime,
+                    O::BulkLeaseQueryQueryEndTime,
+                    O::Ipv6OnlyPreferred,
+                ],
+                any::<u32>(),
+            ),
+            one_of(
+                vec![O::DomainSearch, O::BcmsControllerNames],
+                prop::collection::vec(name(), 1..4),
+            ),
+            any::<i32>().prop_map(O::TimeOffset),
+            prop::collection::vec(any::<u16>(), 1..8).prop_map(O::PathMtuPlateauTable),
+            (MIN_INTERFACE_MTU..=u16::MAX).prop_map(O::InterfaceMtu),
+            any::<u8>().prop_map(|n| O::NetBiosNodeType(n.into())),
+            any::<MessageType>().prop_map(O::MessageType),
+            prop::collection::vec(any::<OptionCode>(), 1..32).prop_map(O::ParameterRequestList),
+            (any::<bool>(), ipv4s()).prop_map(|(m, ips)| O::SlpDirectoryAgent(m, ips)),
+            (any::<bool>(), label()).prop_map(|(m, scope)| O::SlpServiceScope(m, scope)),
+            Just(O::RapidCommit),
+            (any::<u8>(), any::<u8>(), any::<u8>(), name()).prop_map(|(flags, r1, r2, domain)| {
+                // only the canonical wire encoding of the name roundtrips
+                let flags = fqdn::FqdnFlags::new(flags).set_e(true);
+                let mut fqdn = fqdn::ClientFQDN::new(flags, domain);
+                fqdn.set_r1(r1).set_r2(r2);
+                O::ClientFQDN(fqdn)
+            }),
+            any::<RelayAgentInformation>().prop_map(O::RelayAgentInformation),
+            any::<u16>().prop_map(|n| O::ClientSystemArchitecture(n.into())),
+            (any::<u8>(), any::<u8>(), any::<u8>())
+                .prop_map(|(ty, major, minor)| O::ClientNetworkInterface(ty, major, minor)),
+            label().prop_map(|l| O::CaptivePortal(
+                format!("https://{l}.example/capport").parse().unwrap()
+            )),
+            prop::collection::vec((any::<Ipv4Addr>(), 0..=32u8, any::<Ipv4Addr>()), 1..8).prop_map(
+                |routes| O::ClasslessStaticRoute(
+                    routes
+                        .into_iter()
+                        .map(|(dest, prefix, gw)| (Ipv4Net::new(dest, prefix).unwrap().trunc(), gw))
+                        .collect()
+                )
+            ),
+            (any::<u8>(), label())
+                .prop_map(|(code, msg)| O::BulkLeaseQueryStatusCode(code.into(), msg)),
+            any::<u8>().prop_map(|n| O::BulkLeaseQueryDhcpState(n.into())),
+            any::<u8>()
+                .prop_map(|n| O::BulkLeaseQueryDataSource(bulk_query::DataSourceFlags::new(n))),
+            label().prop_map(|l| O::MudUrl(format!("https://{l}.example/mud.json"))),
+            (any::<bool>(), label()).prop_map(|(tls, l)| {
+                let scheme = if tls { "https" } else { "http" };
+                O::WpadUrl(format!("{scheme}://{l}.example/wpad.dat"))
+            }),
+            (
+                any::<u8>().prop_filter("unknown code", |code| matches!(
+                    OptionCode::from(*code),
+                    OptionCode::Unknown(_)
+                )),
+                bytes()
+            )
+                .prop_map(|(code, data)| O::Unknown(UnknownOption::new(code.into(), data))),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for DhcpOptions {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        // a message type is always present
+        (
+            any::<MessageType>(),
+            prop::collection::vec(any::<DhcpOption>(), 0..16),
+        )
+            .prop_map(|(msg_type, opts)| {
+                let mut opts = opts.into_iter().collect::<DhcpOptions>();
+                opts.insert(DhcpOption::MessageType(msg_type));
+                opts
+            })
+            .boxed()
+    }
+}
+
+// sname/file are nul terminated, so the value can't contain a nul
+fn nul_terminated(max: usize) -> impl Strategy<Value = Option<Vec<u8>>> {
+    prop::option::of(
+        prop::collection::vec(1..=u8::MAX, 1..max).prop_map(|mut bytes| {
+            bytes.push(0);
+            bytes
+        }),
+    )
+}
+
+impl Arbitrary for Message {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            (
+                any::<Opcode>(),
+                any::<HType>(),
+                0..=16u8,
+                any::<u8>(),
+                any::<u32>(),
+                any::<u16>(),
+                any::<Flags>(),
+            ),
+            (
+                any::<Ipv4Addr>(),
+                any::<Ipv4Addr>(),
+                any::<Ipv4Addr>(),
+                any::<Ipv4Addr>(),
+                any::<[u8; 16]>(),
+            ),
+            nul_terminated(64),
+            nul_terminated(128),
+            any::<DhcpOptions>(),
+        )
+            .prop_map(
+                |(
+                    (opcode, htype, hlen, hops, xid, secs, flags),
+                    (ciaddr, yiaddr, siaddr, giaddr, chaddr),
+                    sname,
+                    fname,
+                    opts,
+                )| Message {
+                    opcode,
+                    htype,
+                    hlen,
+                    hops,
+                    xid,
+                    secs,
+                    flags,
+                    ciaddr,
+                    yiaddr,
+                    siaddr,
+                    giaddr,
+                    chaddr,
+                    sname,
+                    fname,
+                    magic: MAGIC,
+                    opts,
+                },
+            )
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decodable, Encodable};
+
+    proptest! {
+        #[test]
+        fn roundtrip_message(msg in any::<Message>()) {
+            let buf = msg.to_vec().unwrap();
+            prop_assert_eq!(Message::from_bytes(&buf).unwrap(), msg);
+        }
+
+        #[test]
+        fn roundtrip_option(opt in any::<DhcpOption>()) {
+            let buf = opt.to_vec().unwrap();
+            prop_assert_eq!(DhcpOption::from_bytes(&buf).unwrap(), opt);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! Builders for messages with fixed field requirements
+use std::net::Ipv4Addr;
+
+use crate::{
+    duration::DhcpDuration,
+    error::BuildError,
+    v4::{DhcpOption, Flags, Message, MessageType, Opcode, OptionCode},
+};
+
+/// Builds a DHCPINFORM, sent by a client that already has an address to ask
+/// for other configuration. Created with [`Message::inform`].
+///
+/// `ciaddr` is the client's address, `yiaddr`/`siaddr`/`giaddr` are zero and
+/// options the client MUST NOT send in an INFORM are rejected
+/// <https://www.rfc-editor.org/rfc/rfc2131#section-4.4.1>
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use dhcproto::v4::{DhcpOption, Message, OptionCode};
+///
+/// let msg = Message::inform([192, 168, 0, 10].into(), &[1, 2, 3, 4, 5, 6], 1234)
+///     .opt(DhcpOption::ParameterRequestList(vec![
+///         OptionCode::DomainNameServer,
+///         OptionCode::NtpServers,
+///     ]))?
+///     .build()?;
+/// assert_eq!(msg.ciaddr(), std::net::Ipv4Addr::new(192, 168, 0, 10));
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InformBuilder {
+    msg: Message,
+}
+
+impl InformBuilder {
+    pub(crate) fn new(ciaddr: Ipv4Addr, chaddr: &[u8], xid: u32) -> Self {
+        let mut msg = Message::new_with_id(
+            xid,
+            ciaddr,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            chaddr,
+        );
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Inform));
+        Self { msg }
+    }
+
+    /// add an option, returns an error for the message type, requested
+    /// address, lease time & server identifier options
+    pub fn opt(mut self, opt: DhcpOption) -> Result<Self, BuildError> {
+        let code = OptionCode::from(&opt);
+        match code {
+            OptionCode::MessageType
+            | OptionCode::RequestedIpAddress
+            | OptionCode::AddressLeaseTime
+            | OptionCode::ServerIdentifier => Err(BuildError::OptionNotAllowed {
+                code,
+                msg_type: MessageType::Inform,
+            }),
+            _ => {
+                self.msg.opts_mut().insert(opt);
+                Ok(self)
+            }
+        }
+    }
+
+    /// set `secs`
+    pub fn secs(mut self, secs: u16) -> Self {
+        self.msg.set_secs(secs);
+        self
+    }
+
+    /// Returns the INFORM, or an error if `ciaddr` is unspecified
+    pub fn build(self) -> Result<Message, BuildError> {
+        if self.msg.ciaddr().is_unspecified() {
+            return Err(BuildError::UnspecifiedCiaddr);
+        }
+        Ok(self.msg)
+    }
+}
+
+/// Build a DHCPNAK in reply to `request`, see [`Message::nak`]
+pub(crate) fn nak(
+    request: &Message,
+    server_id: Ipv4Addr,
+    message: Option<&str>,
+) -> Result<Message, BuildError> {
+    match request.opts().msg_type() {
+        Some(MessageType::Request) => {}
+        other => return Err(BuildError::UnexpectedMessageType(other)),
+    }
+    let mut msg = Message::new_with_id(
+        request.xid(),
+        Ipv4Addr::UNSPECIFIED,
+        Ipv4Addr::UNSPECIFIED,
+        Ipv4Addr::UNSPECIFIED,
+        request.giaddr(),
+        request.chaddr(),
+    );
+    msg.set_opcode(Opcode::BootReply)
+        .set_htype(request.htype())
+        // the client has no usable address, the NAK is broadcast by us or
+        // the relay
+        .set_flags(Flags::default().set_broadcast());
+    let opts = msg.opts_mut();
+    opts.insert(DhcpOption::MessageType(MessageType::Nak));
+    opts.insert(DhcpOption::ServerIdentifier(server_id));
+    if let Some(message) = message {
+        opts.insert(DhcpOption::Message(message.to_owned()));
+    }
+    if let Some(id) = request.opts().get(OptionCode::ClientIdentifier) {
+        opts.insert(id.clone());
+    }
+    Ok(msg)
+}
+
+/// Build a DHCPOFFER or DHCPACK in reply to `request`, see
+/// [`Message::build_offer`] & [`Message::build_ack`]
+pub(crate) fn lease_reply(
+    request: &Message,
+    expected: MessageType,
+    reply: MessageType,
+    yiaddr: Ipv4Addr,
+    server_id: Ipv4Addr,
+    lease_time: DhcpDuration,
+) -> Result<Message, BuildError> {
+    match request.opts().msg_type() {
+        Some(ty) if ty == expected => {}
+        other => return Err(BuildError::UnexpectedMessageType(other)),
+    }
+    // ciaddr is zero in an OFFER, an ACK echoes it for renewing clients
+    // <https://www.rfc-editor.org/rfc/rfc2131#section-4.3.1>
+    let ciaddr = match reply {
+        MessageType::Ack => request.ciaddr(),
+        _ => Ipv4Addr::UNSPECIFIED,
+    };
+    let mut msg = Message::new_with_id(
+        request.xid(),
+        ciaddr,
+        yiaddr,
+        Ipv4Addr::UNSPECIFIED,
+        request.giaddr(),
+        request.chaddr(),
+    );
+    msg.set_opcode(Opcode::BootReply)
+        .set_htype(request.htype())
+        .set_flags(request.flags());
+    let opts = msg.opts_mut();
+    opts.insert(DhcpOption::MessageType(reply));
+    opts.insert(DhcpOption::ServerIdentifier(server_id));
+    opts.insert(DhcpOption::AddressLeaseTime(lease_time.as_secs()));
+    // <https://www.rfc-editor.org/rfc/rfc6842>
+    if let Some(id) = request.opts().get(OptionCode::ClientIdentifier) {
+        opts.insert(id.clone());
+    }
+    Ok(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inform() -> Result<(), BuildError> {
+        let ciaddr = Ipv4Addr::new(192, 168, 0, 10);
+        let msg = Message::inform(ciaddr, &[1, 2, 3, 4, 5, 6], 1)
+            .opt(DhcpOption::Hostname("foo".to_owned()))?
+            .build()?;
+        assert_eq!(msg.ciaddr(), ciaddr);
+        assert_eq!(msg.xid(), 1);
+        assert!(msg.yiaddr().is_unspecified());
+        assert!(msg.opts().has_msg_type(MessageType::Inform));
+
+        assert_eq!(
+            Message::inform(ciaddr, &[1, 2, 3, 4, 5, 6], 1)
+                .opt(DhcpOption::AddressLeaseTime(60))
+                .unwrap_err(),
+            BuildError::OptionNotAllowed {
+                code: OptionCode::AddressLeaseTime,
+                msg_type: MessageType::Inform
+            }
+        );
+        assert_eq!(
+            Message::inform(Ipv4Addr::UNSPECIFIED, &[1, 2, 3, 4, 5, 6], 1).build(),
+            Err(BuildError::UnspecifiedCiaddr)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nak() -> Result<(), BuildError> {
+        let server_id = Ipv4Addr::new(192, 168, 0, 1);
+        let mut req = Message::new(
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::new(192, 168, 0, 10),
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            &[1, 2, 3, 4, 5, 6],
+        );
+        req.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Request));
+        req.opts_mut()
+            .insert(DhcpOption::ClientIdentifier(vec![1, 2, 3]));
+
+        let nak = Message::nak(&req, server_id, Some("wrong network"))?;
+        assert_eq!(nak.opcode(), Opcode::BootReply);
+        assert_eq!(nak.xid(), req.xid());
+        assert_eq!(nak.chaddr(), req.chaddr());
+        assert!(nak.yiaddr().is_unspecified());
+        // giaddr is zero, so it's broadcast
+        assert!(nak.flags().broadcast());
+        assert!(nak.opts().has_msg_type(MessageType::Nak));
+        assert_eq!(
+            nak.opts().get(OptionCode::ServerIdentifier),
+            Some(&DhcpOption::ServerIdentifier(server_id))
+        );
+        assert_eq!(
+            nak.opts().get(OptionCode::Message),
+            Some(&DhcpOption::Message("wrong network".to_owned()))
+        );
+        assert!(nak.opts().get(OptionCode::ClientIdentifier).is_some());
+
+        // relayed requests keep giaddr, the relay broadcasts it
+        let giaddr = Ipv4Addr::new(10, 0, 0, 1);
+        req.set_giaddr(giaddr);
+        let nak = Message::nak(&req, server_id, None)?;
+        assert_eq!(nak.giaddr(), giaddr);
+        assert!(nak.flags().broadcast());
+        assert!(nak.opts().get(OptionCode::Message).is_none());
+
+        req.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Discover));
+        assert_eq!(
+            Message::nak(&req, server_id, None),
+            Err(BuildError::UnexpectedMessageType(Some(
+                MessageType::Discover
+            )))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_offer_ack() -> Result<(), BuildError> {
+        let server_id = Ipv4Addr::new(192, 168, 0, 1);
+        let yiaddr = Ipv4Addr::new(192, 168, 0, 10);
+        let lease = DhcpDuration::from_secs(3600);
+        let mut req = Message::default();
+        req.set_flags(Flags::default().set_broadcast());
+        req.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Discover));
+        req.opts_mut()
+            .insert(DhcpOption::ClientIdentifier(vec![1, 2, 3]));
+
+        let offer = Message::build_offer(&req, yiaddr, server_id, lease)?;
+        assert_eq!(offer.opcode(), Opcode::BootReply);
+        assert_eq!(offer.xid(), req.xid());
+        assert_eq!(offer.chaddr(), req.chaddr());
+        assert_eq!(offer.yiaddr(), yiaddr);
+        assert!(offer.flags().broadcast());
+        assert!(offer.opts().has_msg_type(MessageType::Offer));
+        assert_eq!(
+            offer.opts().get(OptionCode::AddressLeaseTime),
+            Some(&DhcpOption::AddressLeaseTime(3600))
+        );
+        assert_eq!(
+            offer.opts().get(OptionCode::ServerIdentifier),
+            Some(&DhcpOption::ServerIdentifier(server_id))
+        );
+        assert!(offer.opts().get(OptionCode::ClientIdentifier).is_some());
+        assert_eq!(
+            Message::build_ack(&req, yiaddr, server_id, lease),
+            Err(BuildError::UnexpectedMessageType(Some(
+                MessageType::Discover
+            )))
+        );
+
+        // renewing client
+        req.set_ciaddr(yiaddr).set_flags(Flags::default());
+        req.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Request));
+        let ack = Message::build_ack(&req, yiaddr, server_id, lease)?;
+        assert!(ack.opts().has_msg_type(MessageType::Ack));
+        assert_eq!(ack.ciaddr(), yiaddr);
+        assert!(!ack.flags().broadcast());
+        assert!(Message::build_offer(&req, yiaddr, server_id, lease).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,676 @@
+//! An I/O-agnostic DHCPv4 client state machine
+//! <https://www.rfc-editor.org/rfc/rfc2131#section-4.4>
+//!
+//! [`DhcpClientFsm`] doesn't own a socket or any timers. Feed it
+//! [`DhcpEvent`]s (a received message, a timer firing) and perform the
+//! [`DhcpAction`]s it returns.
+//!
+//! ```rust
+//! use dhcproto::v4::client::{DhcpAction, DhcpClientFsm, DhcpEvent, State};
+//!
+//! let mut client = DhcpClientFsm::new(&[0xde, 0xad, 0xbe, 0xef, 0x00, 0x01]);
+//! let actions = client.handle(DhcpEvent::Start);
+//! assert_eq!(client.state(), State::Selecting);
+//! assert!(matches!(actions[0], DhcpAction::Broadcast(_)));
+//! ```
+use std::{net::Ipv4Addr, time::Duration};
+
+use crate::{
+    duration::calculate_t1_t2,
+    v4::{DhcpOption, Flags, Message, MessageType, Opcode, OptionCode},
+    DhcpDuration,
+};
+
+/// initial retransmission delay, doubled on each attempt
+/// <https://www.rfc-editor.org/rfc/rfc2131#section-4.1>
+const INITIAL_BACKOFF: Duration = Duration::from_secs(4);
+/// maximum retransmission delay
+const MAX_BACKOFF: Duration = Duration::from_secs(64);
+/// how many times a REQUEST is sent before starting over
+const MAX_REQUESTS: u32 = 4;
+/// delay before restarting after a DECLINE
+/// <https://www.rfc-editor.org/rfc/rfc2131#section-3.1>
+const DECLINE_BACKOFF: Duration = Duration::from_secs(10);
+/// minimum delay between retransmissions while renewing/rebinding
+/// <https://www.rfc-editor.org/rfc/rfc2131#section-4.4.5>
+const MIN_RENEW_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Client states from the state-transition diagram in RFC 2131
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum State {
+    /// no lease, ready to send DISCOVER
+    Init,
+    /// DISCOVER sent, waiting for an OFFER
+    Selecting,
+    /// REQUEST sent for an OFFER, waiting for an ACK
+    Requesting,
+    /// holding a lease
+    Bound,
+    /// T1 expired, unicasting REQUEST to the leasing server
+    Renewing,
+    /// T2 expired, broadcasting REQUEST to any server
+    Rebinding,
+    /// has a previous address, ready to send REQUEST for it
+    InitReboot,
+    /// REQUEST sent for a previous address, waiting for an ACK
+    Rebooting,
+    /// the offered address was declined, waiting to restart
+    Declined,
+    /// lease was released
+    Released,
+}
+
+/// Timers the caller runs on behalf of the state machine
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Timer {
+    /// retransmit the last message
+    Retransmit,
+    /// T1, start renewing
+    Renewal,
+    /// T2, start rebinding
+    Rebinding,
+    /// the lease has expired
+    LeaseExpired,
+}
+
+/// Inputs to [`DhcpClientFsm::handle`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DhcpEvent {
+    /// start (or restart) acquiring a lease
+    Start,
+    /// a message was received from a server
+    Received(Message),
+    /// a timer set with [`DhcpAction::SetTimer`] fired
+    Timeout(Timer),
+    /// the bound address is in use (i.e. found with ARP), send DECLINE
+    Decline,
+    /// give up the lease, send RELEASE
+    Release,
+}
+
+/// Outputs from [`DhcpClientFsm::handle`], to be performed in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DhcpAction {
+    /// broadcast a message
+    Broadcast(Message),
+    /// unicast a message to a server
+    Unicast(Ipv4Addr, Message),
+    /// (re)start a timer, replacing it if already running
+    SetTimer(Timer, Duration),
+    /// stop a timer
+    CancelTimer(Timer),
+    /// configure the interface with a new lease
+    Bind(Lease),
+    /// remove the address from the interface
+    Unbind,
+}
+
+/// A lease taken from a DHCPACK
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lease {
+    /// the leased address (`yiaddr`)
+    pub addr: Ipv4Addr,
+    /// the server that granted the lease
+    pub server_id: Ipv4Addr,
+    /// lease length
+    pub lease_time: DhcpDuration,
+    /// time until renewing (T1)
+    pub renewal: DhcpDuration,
+    /// time until rebinding (T2)
+    pub rebinding: DhcpDuration,
+    /// the ACK, for any other configuration (routers, dns, etc)
+    pub ack: Message,
+}
+
+impl Lease {
+    /// Create a lease from a DHCPACK. Returns `None` if the ACK is missing
+    /// the server identifier or lease time. T1/T2 are calculated with
+    /// [`calculate_t1_t2`].
+    pub fn from_ack(ack: Message) -> Option<Self> {
+        let server_id = match ack.opts().get(OptionCode::ServerIdentifier)? {
+            DhcpOption::ServerIdentifier(ip) => *ip,
+            _ => return None,
+        };
+        let lease_time = match ack.opts().get(OptionCode::AddressLeaseTime)? {
+            DhcpOption::AddressLeaseTime(secs) => DhcpDuration::from(*secs),
+            _ => return None,
+        };
+        let renewal = match ack.opts().get(OptionCode::Renewal) {
+            Some(DhcpOption::Renewal(secs)) => Some(DhcpDuration::from(*secs)),
+            _ => None,
+        };
+        let rebinding = match ack.opts().get(OptionCode::Rebinding) {
+            Some(DhcpOption::Rebinding(secs)) => Some(DhcpDuration::from(*secs)),
+            _ => None,
+        };
+        // ignore out of order values from the server & use the defaults
+        let (renewal, rebinding) = calculate_t1_t2(lease_time, renewal, rebinding)
+            .or_else(|_| calculate_t1_t2(lease_time, None, None))
+            .ok()?;
+        Some(Self {
+            addr: ack.yiaddr(),
+            server_id,
+            lease_time,
+            renewal,
+            rebinding,
+            ack,
+        })
+    }
+
+    /// subnet mask (1) from the ACK
+    pub fn subnet_mask(&self) -> Option<Ipv4Addr> {
+        match self.ack.opts().get(OptionCode::SubnetMask) {
+            Some(DhcpOption::SubnetMask(mask)) => Some(*mask),
+            _ => None,
+        }
+    }
+
+    /// routers (3) from the ACK, in order of preference
+    pub fn routers(&self) -> &[Ipv4Addr] {
+        match self.ack.opts().get(OptionCode::Router) {
+            Some(DhcpOption::Router(ips)) => ips,
+            _ => &[],
+        }
+    }
+
+    /// DNS servers (6) from the ACK, in order of preference
+    pub fn dns_servers(&self) -> &[Ipv4Addr] {
+        match self.ack.opts().get(OptionCode::DomainNameServer) {
+            Some(DhcpOption::DomainNameServer(ips)) => ips,
+            _ => &[],
+        }
+    }
+}
+
+/// A DHCPv4 client state machine, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct DhcpClientFsm {
+    state: State,
+    chaddr: Vec<u8>,
+    xid: u32,
+    params: Vec<OptionCode>,
+    /// address requested in INIT-REBOOT
+    prev_addr: Option<Ipv4Addr>,
+    /// OFFER we're requesting
+    offer: Option<(Ipv4Addr, Ipv4Addr)>,
+    lease: Option<Lease>,
+    attempts: u32,
+}
+
+impl DhcpClientFsm {
+    /// Create a new client in [`State::Init`]
+    /// # Panic
+    ///   panics if chaddr is greater len than 16
+    pub fn new(chaddr: &[u8]) -> Self {
+        assert!(chaddr.len() <= 16);
+        Self {
+            state: State::Init,
+            chaddr: chaddr.to_vec(),
+            xid: rand::random(),
+            params: vec![
+                OptionCode::SubnetMask,
+                OptionCode::Router,
+                OptionCode::DomainNameServer,
+                OptionCode::DomainName,
+            ],
+            prev_addr: None,
+            offer: None,
+            lease: None,
+            attempts: 0,
+        }
+    }
+
+    /// Create a new client in [`State::InitReboot`] that will try to reuse
+    /// a previously leased address
+    /// # Panic
+    ///   panics if chaddr is greater len than 16
+    pub fn with_previous_addr(chaddr: &[u8], addr: Ipv4Addr) -> Self {
+        let mut client = Self::new(chaddr);
+        client.state = State::InitReboot;
+        client.prev_addr = Some(addr);
+        client
+    }
+
+    /// set the options requested in the parameter request list
+    pub fn set_params(&mut self, params: Vec<OptionCode>) -> &mut Self {
+        self.params = params;
+        self
+    }
+
+    /// current state
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// transaction id of the current exchange
+    pub fn xid(&self) -> u32 {
+        self.xid
+    }
+
+    /// the current lease, if bound, renewing or rebinding
+    pub fn lease(&self) -> Option<&Lease> {
+        self.lease.as_ref()
+    }
+
+    /// Process an event, returning the actions to perform. Events that
+    /// aren't valid in the current state (i.e. an OFFER while bound) or
+    /// messages for a different transaction are ignored.
+    pub fn handle(&mut self, event: DhcpEvent) -> Vec<DhcpAction> {
+        use DhcpEvent as E;
+        use State as S;
+        match (self.state, event) {
+            (S::Init | S::Declined | S::Released, E::Start)
+            | (S::Declined, E::Timeout(Timer::Retransmit)) => self.discover(),
+            (S::InitReboot, E::Start) => self.reboot(),
+            (_, E::Start) => Vec::new(),
+            (S::Selecting, E::Timeout(Timer::Retransmit)) => self.retransmit_discover(),
+            (S::Requesting | S::Rebooting, E::Timeout(Timer::Retransmit)) => {
+                if self.attempts >= MAX_REQUESTS {
+                    self.restart()
+                } else {
+                    self.retransmit_request()
+                }
+            }
+            (S::Renewing | S::Rebinding, E::Timeout(Timer::Retransmit)) => {
+                self.retransmit_request()
+            }
+            (S::Bound, E::Timeout(Timer::Renewal)) => {
+                self.state = S::Renewing;
+                self.xid = rand::random();
+                self.attempts = 0;
+                self.retransmit_request()
+            }
+            (S::Bound | S::Renewing, E::Timeout(Timer::Rebinding)) => {
+                self.state = S::Rebinding;
+                self.attempts = 0;
+                self.retransmit_request()
+            }
+            (S::Bound | S::Renewing | S::Rebinding, E::Timeout(Timer::LeaseExpired)) => {
+                let mut actions = vec![DhcpAction::Unbind];
+                actions.extend(self.restart());
+                actions
+            }
+            (_, E::Timeout(_)) => Vec::new(),
+            (_, E::Received(msg)) => self.received(msg),
+            (S::Bound, E::Decline) => self.decline(),
+            (_, E::Decline) => Vec::new(),
+            (S::Bound | S::Renewing | S::Rebinding, E::Release) => self.release(),
+            (_, E::Release) => Vec::new(),
+        }
+    }
+
+    fn received(&mut self, msg: Message) -> Vec<DhcpAction> {
+        use State as S;
+        if msg.opcode() != Opcode::BootReply || msg.xid() != self.xid {
+            return Vec::new();
+        }
+        match (self.state, msg.opts().msg_type()) {
+            (S::Selecting, Some(MessageType::Offer)) => {
+                let server_id = match msg.opts().get(OptionCode::ServerIdentifier) {
+                    Some(DhcpOption::ServerIdentifier(ip)) => *ip,
+                    _ => return Vec::new(),
+                };
+                self.offer = Some((msg.yiaddr(), server_id));
+                self.state = S::Requesting;
+                self.attempts = 0;
+                self.retransmit_request()
+            }
+            (S::Requesting | S::Rebooting | S::Renewing | S::Rebinding, Some(MessageType::Ack)) => {
+                match Lease::from_ack(msg) {
+                    Some(lease) => self.bind(lease),
+                    None => Vec::new(),
+                }
+            }
+            (S::Requesting | S::Rebooting, Some(MessageType::Nak)) => self.restart(),
+            (S::Renewing | S::Rebinding, Some(MessageType::Nak)) => {
+                let mut actions = vec![DhcpAction::Unbind];
+                actions.extend(self.restart());
+                actions
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn bind(&mut self, lease: Lease) -> Vec<DhcpAction> {
+        self.state = State::Bound;
+        self.offer = None;
+        self.prev_addr = None;
+        self.attempts = 0;
+        let mut actions = vec![DhcpAction::CancelTimer(Timer::Retransmit)];
+        for (timer, time) in [
+            (Timer::Renewal, lease.renewal),
+            (Timer::Rebinding, lease.rebinding),
+            (Timer::LeaseExpired, lease.lease_time),
+        ] {
+            actions.push(if time.is_infinite() {
+                DhcpAction::CancelTimer(timer)
+            } else {
+                DhcpAction::SetTimer(timer, time.into())
+            });
+        }
+        actions.push(DhcpAction::Bind(lease.clone()));
+        self.lease = Some(lease);
+        actions
+    }
+
+    // back to INIT, immediately sending a new DISCOVER
+    fn restart(&mut self) -> Vec<DhcpAction> {
+        self.state = State::Init;
+        self.lease = None;
+        self.offer = None;
+        self.prev_addr = None;
+        let mut actions = vec![
+            DhcpAction::CancelTimer(Timer::Renewal),
+            DhcpAction::CancelTimer(Timer::Rebinding),
+            DhcpAction::CancelTimer(Timer::LeaseExpired),
+        ];
+        actions.extend(self.discover());
+        actions
+    }
+
+    fn discover(&mut self) -> Vec<DhcpAction> {
+        self.state = State::Selecting;
+        self.xid = rand::random();
+        self.attempts = 0;
+        self.retransmit_discover()
+    }
+
+    fn retransmit_discover(&mut self) -> Vec<DhcpAction> {
+        let mut msg = self.message(MessageType::Discover);
+        msg.opts_mut()
+            .insert(DhcpOption::ParameterRequestList(self.params.clone()));
+        let backoff = self.backoff();
+        vec![
+            DhcpAction::Broadcast(msg),
+            DhcpAction::SetTimer(Timer::Retransmit, backoff),
+        ]
+    }
+
+    fn reboot(&mut self) -> Vec<DhcpAction> {
+        self.state = State::Rebooting;
+        self.xid = rand::random();
+        self.attempts = 0;
+        self.retransmit_request()
+    }
+
+    // sends the REQUEST appropriate for the current state
+    // <https://www.rfc-editor.org/rfc/rfc2131#section-4.3.2>
+    fn retransmit_request(&mut self) -> Vec<DhcpAction> {
+        let mut msg = self.message(MessageType::Request);
+        msg.opts_mut()
+            .insert(DhcpOption::ParameterRequestList(self.params.clone()));
+        let backoff = self.backoff();
+        let send = match self.state {
+            State::Requesting => {
+                let (addr, server_id) = self.offer.expect("requesting without an offer");
+                msg.opts_mut().insert(DhcpOption::RequestedIpAddress(addr));
+                msg.opts_mut()
+                    .insert(DhcpOption::ServerIdentifier(server_id));
+                DhcpAction::Broadcast(msg)
+            }
+            State::Rebooting => {
+                let addr = self.prev_addr.expect("rebooting without an address");
+                msg.opts_mut().insert(DhcpOption::RequestedIpAddress(addr));
+                DhcpAction::Broadcast(msg)
+            }
+            State::Renewing | State::Rebinding => {
+                let lease = self.lease.as_ref().expect("renewing without a lease");
+                msg.set_ciaddr(lease.addr);
+                let server_id = lease.server_id;
+                return vec![
+                    if self.state == State::Renewing {
+                        DhcpAction::Unicast(server_id, msg)
+                    } else {
+                        DhcpAction::Broadcast(msg)
+                    },
+                    DhcpAction::SetTimer(Timer::Retransmit, backoff.max(MIN_RENEW_BACKOFF)),
+                ];
+            }
+            state => unreachable!("REQUEST sent in {state:?}"),
+        };
+        vec![send, DhcpAction::SetTimer(Timer::Retransmit, backoff)]
+    }
+
+    fn decline(&mut self) -> Vec<DhcpAction> {
+        let lease = self.lease.take().expect("bound without a lease");
+        let mut msg = self.message(MessageType::Decline);
+        msg.opts_mut()
+            .insert(DhcpOption::RequestedIpAddress(lease.addr));
+        msg.opts_mut()
+            .insert(DhcpOption::ServerIdentifier(lease.server_id));
+        self.state = State::Declined;
+        vec![
+            DhcpAction::Unbind,
+            DhcpAction::CancelTimer(Timer::Renewal),
+            DhcpAction::CancelTimer(Timer::Rebinding),
+            DhcpAction::CancelTimer(Timer::LeaseExpired),
+            DhcpAction::Broadcast(msg),
+            DhcpAction::SetTimer(Timer::Retransmit, DECLINE_BACKOFF),
+        ]
+    }
+
+    fn release(&mut self) -> Vec<DhcpAction> {
+        let lease = self.lease.take().expect("bound without a lease");
+        let mut msg = self.message(MessageType::Release);
+        msg.set_ciaddr(lease.addr);
+        msg.opts_mut()
+            .insert(DhcpOption::ServerIdentifier(lease.server_id));
+        self.state = State::Released;
+        vec![
+            DhcpAction::CancelTimer(Timer::Retransmit),
+            DhcpAction::CancelTimer(Timer::Renewal),
+            DhcpAction::CancelTimer(Timer::Rebinding),
+            DhcpAction::CancelTimer(Timer::LeaseExpired),
+            DhcpAction::Unicast(lease.server_id, msg),
+            DhcpAction::Unbind,
+        ]
+    }
+
+    // exponential backoff, counting this attempt
+    fn backoff(&mut self) -> Duration {
+        let backoff = INITIAL_BACKOFF
+            .saturating_mul(1 << self.attempts.min(4))
+            .min(MAX_BACKOFF);
+        self.attempts += 1;
+        backoff
+    }
+
+    fn message(&self, msg_type: MessageType) -> Message {
+        let mut msg = Message::new_with_id(
+            self.xid,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            &self.chaddr,
+        );
+        // only ask for a broadcast reply until we have an address
+        // <https://www.rfc-editor.org/rfc/rfc1542#section-3.1.1>
+        if matches!(
+            self.state,
+            State::Selecting | State::Requesting | State::Rebooting
+        ) {
+            msg.set_flags(Flags::default().set_broadcast());
+        }
+        msg.opts_mut().insert(DhcpOption::MessageType(msg_type));
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHADDR: [u8; 6] = [0xde, 0xad, 0xbe, 0xef, 0x00, 0x01];
+    const SERVER: Ipv4Addr = Ipv4Addr::new(192, 168, 0, 1);
+    const ADDR: Ipv4Addr = Ipv4Addr::new(192, 168, 0, 100);
+
+    fn reply(client: &DhcpClientFsm, msg_type: MessageType) -> Message {
+        let mut msg = Message::new_with_id(
+            client.xid(),
+            Ipv4Addr::UNSPECIFIED,
+            ADDR,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            &CHADDR,
+        );
+        msg.set_opcode(Opcode::BootReply);
+        msg.opts_mut().insert(DhcpOption::MessageType(msg_type));
+        msg.opts_mut().insert(DhcpOption::ServerIdentifier(SERVER));
+        msg.opts_mut().insert(DhcpOption::AddressLeaseTime(3600));
+        msg
+    }
+
+    fn sent(actions: &[DhcpAction]) -> &Message {
+        actions
+            .iter()
+            .find_map(|action| match action {
+                DhcpAction::Broadcast(msg) | DhcpAction::Unicast(_, msg) => Some(msg),
+                _ => None,
+            })
+            .expect("no message sent")
+    }
+
+    fn bound() -> DhcpClientFsm {
+        let mut client = DhcpClientFsm::new(&CHADDR);
+        client.handle(DhcpEvent::Start);
+        let offer = reply(&client, MessageType::Offer);
+        client.handle(DhcpEvent::Received(offer));
+        let ack = reply(&client, MessageType::Ack);
+        client.handle(DhcpEvent::Received(ack));
+        assert_eq!(client.state(), State::Bound);
+        client
+    }
+
+    #[test]
+    fn test_init_to_bound() {
+        let mut client = DhcpClientFsm::new(&CHADDR);
+        let actions = client.handle(DhcpEvent::Start);
+        assert_eq!(client.state(), State::Selecting);
+        assert!(sent(&actions).opts().has_msg_type(MessageType::Discover));
+        assert!(sent(&actions).flags().broadcast());
+        assert_eq!(
+            actions[1],
+            DhcpAction::SetTimer(Timer::Retransmit, INITIAL_BACKOFF)
+        );
+
+        // retransmit backs off
+        let actions = client.handle(DhcpEvent::Timeout(Timer::Retransmit));
+        assert_eq!(
+            actions[1],
+            DhcpAction::SetTimer(Timer::Retransmit, Duration::from_secs(8))
+        );
+
+        // other transactions are ignored
+        let mut offer = reply(&client, MessageType::Offer);
+        offer.set_xid(client.xid().wrapping_add(1));
+        assert!(client.handle(DhcpEvent::Received(offer)).is_empty());
+
+        let offer = reply(&client, MessageType::Offer);
+        let actions = client.handle(DhcpEvent::Received(offer));
+        assert_eq!(client.state(), State::Requesting);
+        let req = sent(&actions);
+        assert!(req.opts().has_msg_type(MessageType::Request));
+        assert!(req.flags().broadcast());
+        assert_eq!(
+            req.opts().get(OptionCode::RequestedIpAddress),
+            Some(&DhcpOption::RequestedIpAddress(ADDR))
+        );
+
+        let ack = reply(&client, MessageType::Ack);
+        let actions = client.handle(DhcpEvent::Received(ack));
+        assert_eq!(client.state(), State::Bound);
+        assert!(actions.contains(&DhcpAction::SetTimer(
+            Timer::Renewal,
+            Duration::from_secs(1800)
+        )));
+        assert!(actions.contains(&DhcpAction::SetTimer(
+            Timer::Rebinding,
+            Duration::from_secs(3150)
+        )));
+        assert!(matches!(actions.last(), Some(DhcpAction::Bind(lease)) if lease.addr == ADDR));
+    }
+
+    #[test]
+    fn test_renew_rebind_expire() {
+        let mut client = bound();
+        let actions = client.handle(DhcpEvent::Timeout(Timer::Renewal));
+        assert_eq!(client.state(), State::Renewing);
+        assert!(
+            matches!(&actions[0], DhcpAction::Unicast(ip, msg) if *ip == SERVER && msg.ciaddr() == ADDR)
+        );
+        // we have an address, so no broadcast bit
+        assert!(!sent(&actions).flags().broadcast());
+
+        let actions = client.handle(DhcpEvent::Timeout(Timer::Rebinding));
+        assert_eq!(client.state(), State::Rebinding);
+        assert!(matches!(&actions[0], DhcpAction::Broadcast(_)));
+        assert!(!sent(&actions).flags().broadcast());
+
+        let ack = reply(&client, MessageType::Ack);
+        client.handle(DhcpEvent::Received(ack));
+        assert_eq!(client.state(), State::Bound);
+
+        client.handle(DhcpEvent::Timeout(Timer::Renewal));
+        let actions = client.handle(DhcpEvent::Timeout(Timer::LeaseExpired));
+        assert_eq!(actions[0], DhcpAction::Unbind);
+        assert_eq!(client.state(), State::Selecting);
+        assert!(client.lease().is_none());
+    }
+
+    #[test]
+    fn test_nak_restarts() {
+        let mut client = DhcpClientFsm::with_previous_addr(&CHADDR, ADDR);
+        assert_eq!(client.state(), State::InitReboot);
+        let actions = client.handle(DhcpEvent::Start);
+        assert_eq!(client.state(), State::Rebooting);
+        let req = sent(&actions);
+        assert!(req.opts().get(OptionCode::ServerIdentifier).is_none());
+
+        let nak = reply(&client, MessageType::Nak);
+        let actions = client.handle(DhcpEvent::Received(nak));
+        assert_eq!(client.state(), State::Selecting);
+        assert!(sent(&actions).opts().has_msg_type(MessageType::Discover));
+    }
+
+    #[test]
+    fn test_lease_from_ack() {
+        let client = DhcpClientFsm::new(&CHADDR);
+        let mut ack = reply(&client, MessageType::Ack);
+        let lease = Lease::from_ack(ack.clone()).unwrap();
+        assert_eq!(lease.addr, ADDR);
+        assert_eq!(lease.renewal, DhcpDuration::from_secs(1800));
+        assert_eq!(lease.subnet_mask(), None);
+        assert!(lease.routers().is_empty());
+
+        let mask = Ipv4Addr::new(255, 255, 255, 0);
+        ack.opts_mut().insert(DhcpOption::SubnetMask(mask));
+        ack.opts_mut().insert(DhcpOption::Router(vec![SERVER]));
+        ack.opts_mut()
+            .insert(DhcpOption::DomainNameServer(vec![SERVER, ADDR]));
+        let lease = Lease::from_ack(ack).unwrap();
+        assert_eq!(lease.subnet_mask(), Some(mask));
+        assert_eq!(lease.routers(), [SERVER]);
+        assert_eq!(lease.dns_servers(), [SERVER, ADDR]);
+    }
+
+    #[test]
+    fn test_decline_release() {
+        let mut client = bound();
+        let actions = client.handle(DhcpEvent::Decline);
+        assert_eq!(client.state(), State::Declined);
+        assert!(sent(&actions).opts().has_msg_type(MessageType::Decline));
+        assert!(!sent(&actions).flags().broadcast());
+        client.handle(DhcpEvent::Timeout(Timer::Retransmit));
+        assert_eq!(client.state(), State::Selecting);
+
+        let mut client = bound();
+        let actions = client.handle(DhcpEvent::Release);
+        assert_eq!(client.state(), State::Released);
+        assert!(
+            matches!(&actions[4], DhcpAction::Unicast(ip, msg) if *ip == SERVER && msg.opts().has_msg_type(MessageType::Release))
+        );
+        assert!(!sent(&actions).flags().broadcast());
+        assert!(client.handle(DhcpEvent::Release).is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+//! DOCSIS cable modem vendor extensions (option 43)
+//!
+//! Cable modems send a class identifier (option 60) starting with `docsis`
+//! and are provisioned with CableLabs (enterprise 4491) sub-options in
+//! option 43.
+use std::net::Ipv4Addr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    encoder::{Encodable, Encoder},
+    error::{DecodeResult, EncodeResult},
+    sub_option::{SubOptionCodec, U8Tlv},
+    v4::{DhcpOption, Message, OptionCode},
+};
+
+/// CableLabs enterprise number
+pub const CABLELABS_ENTERPRISE: u32 = 4491;
+
+const TFTP_SERVER: u8 = 1;
+const CONFIG_FILE: u8 = 2;
+const SYSLOG_SERVER: u8 = 3;
+
+/// DOCSIS sub-options of option 43. Unknown sub-options are ignored when
+/// decoding.
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::net::Ipv4Addr;
+/// use dhcproto::v4::{docsis::DocsisOption43, DhcpOption, Message};
+///
+/// let mut msg = Message::default();
+/// msg.opts_mut()
+///     .insert(DhcpOption::ClassIdentifier(b"docsis3.0".to_vec()));
+/// let docsis = DocsisOption43 {
+///     tftp_server: Some(Ipv4Addr::new(10, 0, 0, 1)),
+///     config_file: Some("modem.cfg".to_owned()),
+///     syslog_server: None,
+/// };
+/// msg.opts_mut().insert(docsis.to_option()?);
+///
+/// assert_eq!(DocsisOption43::from_message(&msg)?, Some(docsis));
+/// # Ok(()) }
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DocsisOption43 {
+    /// 1 - TFTP server address
+    pub tftp_server: Option<Ipv4Addr>,
+    /// 2 - config file path
+    pub config_file: Option<String>,
+    /// 3 - syslog server address
+    pub syslog_server: Option<Ipv4Addr>,
+}
+
+impl DocsisOption43 {
+    /// decode option 43 if the message's class identifier (option 60)
+    /// starts with `docsis`. Returns `None` if either option is missing or
+    /// the client isn't a cable modem.
+    pub fn from_message(msg: &Message) -> DecodeResult<Option<Self>> {
+        match (
+            msg.opts().get(OptionCode::ClassIdentifier),
+            msg.opts().get(OptionCode::VendorExtensions),
+        ) {
+            (
+                Some(DhcpOption::ClassIdentifier(class)),
+                Some(DhcpOption::VendorExtensions(data)),
+            ) if is_docsis(class) => Self::from_bytes(data).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// encode as a [`DhcpOption::VendorExtensions`]
+    pub fn to_option(&self) -> EncodeResult<DhcpOption> {
+        self.to_vec().map(DhcpOption::VendorExtensions)
+    }
+}
+
+/// true if a class identifier (option 60) belongs to a DOCSIS cable modem
+pub fn is_docsis(class: &[u8]) -> bool {
+    class.len() >= 6 && class[..6].eq_ignore_ascii_case(b"docsis")
+}
+
+impl Decodable for DocsisOption43 {
+    fn decode(d: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let mut opt = DocsisOption43::default();
+        for (code, data) in U8Tlv::decode(d)? {
+            let mut d = Decoder::new(&data);
+            match code {
+                TFTP_SERVER => opt.tftp_server = Some(d.read_ipv4(data.len())?),
+                CONFIG_FILE => opt.config_file = Some(d.read_string(data.len())?),
+                SYSLOG_SERVER => opt.syslog_server = Some(d.read_ipv4(data.len())?),
+                _ => {}
+            }
+        }
+        Ok(opt)
+    }
+}
+
+impl Encodable for DocsisOption43 {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        let tftp = self.tftp_server.map(|ip| ip.octets());
+        let syslog = self.syslog_server.map(|ip| ip.octets());
+        let opts = [
+            tftp.as_ref().map(|ip| (TFTP_SERVER, &ip[..])),
+            self.config_file
+                .as_ref()
+                .map(|file| (CONFIG_FILE, file.as_bytes())),
+            syslog.as_ref().map(|ip| (SYSLOG_SERVER, &ip[..])),
+        ];
+        U8Tlv::encode(&opts.into_iter().flatten().collect::<Vec<_>>(), e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_docsis() -> DecodeResult<()> {
+        let buf = [
+            1, 4, 10, 0, 0, 1, // tftp
+            2, 5, b'a', b'.', b'c', b'f', b'g', // config file
+            99, 1, 0, // unknown
+            3, 4, 10, 0, 0, 2, // syslog
+        ];
+        assert_eq!(
+            DocsisOption43::from_bytes(&buf)?,
+            DocsisOption43 {
+                tftp_server: Some(Ipv4Addr::new(10, 0, 0, 1)),
+                config_file: Some("a.cfg".to_owned()),
+                syslog_server: Some(Ipv4Addr::new(10, 0, 0, 2)),
+            }
+        );
+        // bad address length
+        assert!(DocsisOption43::from_bytes(&[1, 3, 10, 0, 0]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn from_message() -> DecodeResult<()> {
+        let mut msg = Message::default();
+        msg.opts_mut()
+            .insert(DhcpOption::VendorExtensions(vec![2, 1, b'a']));
+        assert_eq!(DocsisOption43::from_message(&msg)?, None);
+        msg.opts_mut()
+            .insert(DhcpOption::ClassIdentifier(b"MSFT 5.0".to_vec()));
+        assert_eq!(DocsisOption43::from_message(&msg)?, None);
+        msg.opts_mut()
+            .insert(DhcpOption::ClassIdentifier(b"DOCSIS3.1:05".to_vec()));
+        assert_eq!(
+            DocsisOption43::from_message(&msg)?.and_then(|opt| opt.config_file),
+            Some("a".to_owned())
+        );
+        Ok(())
+    }
+}
@@ -69,6 +69,31 @@ pub enum HType {
     Unknown(u8),
 }
 
+impl HType {
+    /// The expected hardware address length (`hlen`) for this type, if it
+    /// has a fixed length
+    ///
+    /// ```rust
+    /// use dhcproto::v4::HType;
+    ///
+    /// assert_eq!(HType::Eth.hlen_for_type(), Some(6));
+    /// assert_eq!(HType::Unknown(200).hlen_for_type(), None);
+    /// ```
+    pub fn hlen_for_type(&self) -> Option<u8> {
+        use HType::*;
+        match self {
+            Eth | IEEE802 => Some(6),
+            ExperimentalEth | ARCNET | LocalTalk => Some(1),
+            // callsign + SSID
+            AmRadioAX25 => Some(7),
+            // chaddr must be zero, the client id carries the GUID
+            // <https://www.rfc-editor.org/rfc/rfc4390#section-2.1>
+            Infiniband => Some(0),
+            _ => None,
+        }
+    }
+}
+
 impl From<u8> for HType {
     fn from(n: u8) -> Self {
         use HType::*;
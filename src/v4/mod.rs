@@ -80,19 +80,29 @@
 //!
 use std::{fmt, net::Ipv4Addr, str::Utf8Error};
 
+use crate::duration::{calculate_t1_t2, DhcpDuration};
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "proptest")]
+mod arbitrary;
+mod builder;
 pub mod bulk_query;
+pub mod client;
+pub mod docsis;
 mod flags;
 pub mod fqdn;
 mod htype;
+pub mod msft;
 mod opcode;
 mod options;
+mod raw;
 pub mod relay;
+pub mod util;
 
 // re-export submodules from proto::msg
-pub use self::{flags::*, htype::*, opcode::*, options::*};
+pub use self::{builder::*, flags::*, htype::*, opcode::*, options::*, raw::*};
 pub use crate::{
     decoder::{Decodable, Decoder},
     encoder::{Encodable, Encoder},
@@ -101,6 +111,13 @@ pub use crate::{
 
 pub const MAGIC: [u8; 4] = [99, 130, 83, 99];
 pub const MIN_PACKET_SIZE: usize = 300;
+/// length of the options field every client must be able to receive,
+/// without overloading `sname`/`file`
+/// <https://www.rfc-editor.org/rfc/rfc2131#section-2>
+pub const MAX_OPTIONS_LEN: usize = 312;
+/// smallest legal interface MTU (option 26)
+/// <https://www.rfc-editor.org/rfc/rfc2132#section-5.1>
+pub const MIN_INTERFACE_MTU: u16 = 68;
 
 /// default dhcpv4 server port
 pub const SERVER_PORT: u16 = 67;
@@ -300,24 +317,35 @@ impl Message {
     }
 
     /// Get the message's giaddr.
-    /// Gateway IP
+    /// Gateway (relay agent) IP
+    ///
+    /// Set by the first relay agent to forward a client message, zero if the
+    /// client is on the server's link. The server copies it into its reply
+    /// and sends the reply to the relay
+    /// <https://www.rfc-editor.org/rfc/rfc2131#section-4.1>
     pub fn giaddr(&self) -> Ipv4Addr {
         self.giaddr
     }
     /// Set the message's giaddr.
-    /// Gateway IP
+    /// Gateway (relay agent) IP, see [`Message::giaddr`]
     pub fn set_giaddr<I: Into<Ipv4Addr>>(&mut self, giaddr: I) -> &mut Self {
         self.giaddr = giaddr.into();
         self
     }
 
     /// Get the message's siaddr.
-    /// Server IP
+    /// Next server IP
+    ///
+    /// Only set by a server in OFFER & ACK, it's the server the client uses
+    /// next in bootstrap, i.e. a PXE TFTP server. It isn't the address of
+    /// the server replying, that's the server identifier (option 54), and
+    /// relay agents don't change it
+    /// <https://www.rfc-editor.org/rfc/rfc2131#section-2>
     pub fn siaddr(&self) -> Ipv4Addr {
         self.siaddr
     }
     /// Set the message's siaddr.
-    /// Server IP
+    /// Next server IP, see [`Message::siaddr`]
     pub fn set_siaddr<I: Into<Ipv4Addr>>(&mut self, siaddr: I) -> &mut Self {
         self.siaddr = siaddr.into();
         self
@@ -330,7 +358,7 @@ impl Message {
         self.yiaddr
     }
 
-    /// Set the message's siaddr.
+    /// Set the message's yiaddr.
     /// Your IP
     pub fn set_yiaddr<I: Into<Ipv4Addr>>(&mut self, yiaddr: I) -> &mut Self {
         self.yiaddr = yiaddr.into();
@@ -343,7 +371,7 @@ impl Message {
         self.ciaddr
     }
 
-    /// Set the message's siaddr.
+    /// Set the message's ciaddr.
     /// Client IP
     pub fn set_ciaddr<I: Into<Ipv4Addr>>(&mut self, ciaddr: I) -> &mut Self {
         self.ciaddr = ciaddr.into();
@@ -453,6 +481,19 @@ impl Message {
         &self.opts
     }
 
+    /// the message type (53), same as `msg.opts().msg_type()`
+    /// ```
+    /// # use dhcproto::v4::{DhcpOption, Message, MessageType};
+    /// let mut msg = Message::default();
+    /// assert_eq!(msg.msg_type(), None);
+    /// msg.opts_mut()
+    ///     .insert(DhcpOption::MessageType(MessageType::Discover));
+    /// assert_eq!(msg.msg_type(), Some(MessageType::Discover));
+    /// ```
+    pub fn msg_type(&self) -> Option<MessageType> {
+        self.opts.msg_type()
+    }
+
     /// Set the DHCP options
     pub fn set_opts(&mut self, opts: DhcpOptions) -> &mut Self {
         self.opts = opts;
@@ -463,6 +504,260 @@ impl Message {
     pub fn opts_mut(&mut self) -> &mut DhcpOptions {
         &mut self.opts
     }
+
+    /// Start building a DHCPINFORM for a client that already has `ciaddr`,
+    /// see [`InformBuilder`]
+    /// # Panic
+    ///   panics if chaddr is greater len than 16
+    pub fn inform(ciaddr: Ipv4Addr, chaddr: &[u8], xid: u32) -> InformBuilder {
+        InformBuilder::new(ciaddr, chaddr, xid)
+    }
+
+    /// Build a DHCPNAK in reply to a DHCPREQUEST, keeping its `xid`,
+    /// `chaddr`, `giaddr` & client identifier. `message` is sent in option 56.
+    /// The broadcast flag is always set, so a relay agent will broadcast it
+    /// to the client <https://www.rfc-editor.org/rfc/rfc2131#section-4.3.2>
+    ///
+    /// Returns an error if `request` isn't a DHCPREQUEST
+    pub fn nak(
+        request: &Message,
+        server_id: Ipv4Addr,
+        message: Option<&str>,
+    ) -> Result<Message, BuildError> {
+        builder::nak(request, server_id, message)
+    }
+
+    /// Build a DHCPOFFER in reply to a DHCPDISCOVER, offering `yiaddr`.
+    /// Keeps the request's `xid`, `chaddr`, `flags`, `giaddr` & client
+    /// identifier and adds the server identifier & lease time options.
+    ///
+    /// Returns an error if `discover` isn't a DHCPDISCOVER
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use dhcproto::{v4::{DhcpOption, Message, MessageType}, DhcpDuration};
+    ///
+    /// let mut discover = Message::default();
+    /// discover
+    ///     .opts_mut()
+    ///     .insert(DhcpOption::MessageType(MessageType::Discover));
+    /// let offer = Message::build_offer(
+    ///     &discover,
+    ///     [192, 168, 0, 10].into(),
+    ///     [192, 168, 0, 1].into(),
+    ///     DhcpDuration::from_secs(3600),
+    /// )?;
+    /// assert_eq!(offer.xid(), discover.xid());
+    /// assert!(offer.opts().has_msg_type(MessageType::Offer));
+    /// # Ok(()) }
+    /// ```
+    pub fn build_offer(
+        discover: &Message,
+        yiaddr: Ipv4Addr,
+        server_id: Ipv4Addr,
+        lease_time: DhcpDuration,
+    ) -> Result<Message, BuildError> {
+        builder::lease_reply(
+            discover,
+            MessageType::Discover,
+            MessageType::Offer,
+            yiaddr,
+            server_id,
+            lease_time,
+        )
+    }
+
+    /// Build a DHCPACK in reply to a DHCPREQUEST, like [`Message::build_offer`]
+    /// but `ciaddr` is also kept for renewing clients
+    ///
+    /// Returns an error if `request` isn't a DHCPREQUEST
+    pub fn build_ack(
+        request: &Message,
+        yiaddr: Ipv4Addr,
+        server_id: Ipv4Addr,
+        lease_time: DhcpDuration,
+    ) -> Result<Message, BuildError> {
+        builder::lease_reply(
+            request,
+            MessageType::Request,
+            MessageType::Ack,
+            yiaddr,
+            server_id,
+            lease_time,
+        )
+    }
+
+    /// Check the message for inconsistencies that decoding accepts:
+    ///
+    /// * `hlen` must fit in `chaddr` and match the length of a known `htype`
+    /// * `chaddr` must be zero padded after the first `hlen` bytes
+    ///   <https://www.rfc-editor.org/rfc/rfc2131#section-2>
+    /// * with a lease time, renewal & rebinding times must satisfy
+    ///   `T1 < T2 < lease`, see [`calculate_t1_t2`]
+    /// * a DHCPREQUEST with `ciaddr` set (RENEWING or REBINDING) must not
+    ///   have a requested IP address (50)
+    ///   <https://www.rfc-editor.org/rfc/rfc2131#section-4.3.2>
+    /// * a DHCPOFFER, DHCPACK or DHCPNAK must have a server identifier (54)
+    ///   <https://www.rfc-editor.org/rfc/rfc2131#section-4.3.1>
+    /// * a DHCPOFFER, or DHCPACK assigning `yiaddr`, must have a lease time
+    ///   (51). An ACK to a DHCPINFORM has neither
+    /// * a subnet mask (1) must be contiguous, and with a router (3) the
+    ///   first router must be in the client's subnet (`yiaddr`, or `ciaddr`
+    ///   if unset)
+    ///
+    /// ```rust
+    /// use dhcproto::v4::{HType, Message, ValidationError};
+    ///
+    /// let mut msg = Message::default();
+    /// msg.set_chaddr(&[1, 2, 3, 4, 5, 6]);
+    /// assert!(msg.validate().is_ok());
+    /// msg.set_htype(HType::IEEE802).set_chaddr(&[1, 2, 3, 4]);
+    /// assert!(matches!(msg.validate(), Err(ValidationError::HlenMismatch { .. })));
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.hlen as usize > self.chaddr.len() {
+            return Err(ValidationError::HlenTooLong(self.hlen));
+        }
+        if let Some(expected) = self.htype.hlen_for_type() {
+            if self.hlen != expected {
+                return Err(ValidationError::HlenMismatch {
+                    htype: self.htype,
+                    hlen: self.hlen,
+                    expected,
+                });
+            }
+        }
+        if self.chaddr[self.hlen as usize..].iter().any(|b| *b != 0) {
+            return Err(ValidationError::ChaddrNotPadded(self.hlen));
+        }
+        if let Some(lease) = self.opts.lease_time() {
+            let t1 = match self.opts.get(OptionCode::Renewal) {
+                Some(DhcpOption::Renewal(t1)) => Some(DhcpDuration::from_secs(*t1)),
+                _ => None,
+            };
+            let t2 = match self.opts.get(OptionCode::Rebinding) {
+                Some(DhcpOption::Rebinding(t2)) => Some(DhcpDuration::from_secs(*t2)),
+                _ => None,
+            };
+            calculate_t1_t2(lease, t1, t2)?;
+        }
+        if self.opts.has_msg_type(MessageType::Request)
+            && !self.ciaddr.is_unspecified()
+            && self.opts.get(OptionCode::RequestedIpAddress).is_some()
+        {
+            return Err(ValidationError::RequestedIpWithCiaddr);
+        }
+        match self.opts.msg_type() {
+            Some(msg_type @ (MessageType::Offer | MessageType::Ack | MessageType::Nak))
+                if self.opts.get(OptionCode::ServerIdentifier).is_none() =>
+            {
+                return Err(ValidationError::MissingServerIdentifier(msg_type));
+            }
+            _ => {}
+        }
+        let assigns = self.opts.has_msg_type(MessageType::Offer)
+            || (self.opts.has_msg_type(MessageType::Ack) && !self.yiaddr.is_unspecified());
+        if assigns && self.opts.lease_time().is_none() {
+            return Err(ValidationError::MissingLeaseTime);
+        }
+        if let Some(DhcpOption::SubnetMask(mask)) = self.opts.get(OptionCode::SubnetMask) {
+            util::netmask_to_prefix_len(*mask)?;
+            let client = if self.yiaddr.is_unspecified() {
+                self.ciaddr
+            } else {
+                self.yiaddr
+            };
+            let router = match self.opts.get(OptionCode::Router) {
+                Some(DhcpOption::Router(routers)) => routers.first(),
+                _ => None,
+            };
+            if let Some(router) = router {
+                let mask = u32::from(*mask);
+                let subnet = |ip: Ipv4Addr| u32::from(ip) & mask;
+                if !client.is_unspecified() && subnet(*router) != subnet(client) {
+                    return Err(ValidationError::RouterOutsideSubnet(*router));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Prepare a client message to be forwarded to a server by a relay agent
+    /// <https://www.rfc-editor.org/rfc/rfc1542#section-4.1.1>
+    ///
+    /// * `hops` is incremented, returning an error if it would overflow.
+    ///   Nothing is changed in that case
+    /// * `giaddr` is set, unless the message was already relayed
+    /// * `relay_info` is added as option 82, unless a previous relay added one
+    ///   <https://www.rfc-editor.org/rfc/rfc3046#section-2.1>
+    ///
+    /// The message is then unicast to the server, the broadcast flag is left
+    /// as-is so the reply can be delivered the way the client asked.
+    pub fn relay_forward(
+        &mut self,
+        giaddr: Ipv4Addr,
+        relay_info: relay::RelayAgentInformation,
+    ) -> Result<&mut Self, RelayError> {
+        self.hops = self.hops.checked_add(1).ok_or(RelayError::HopsOverflow)?;
+        if self.giaddr.is_unspecified() {
+            self.giaddr = giaddr;
+        }
+        if self.opts.get(OptionCode::RelayAgentInformation).is_none() {
+            self.opts
+                .insert(DhcpOption::RelayAgentInformation(relay_info));
+        }
+        Ok(self)
+    }
+
+    /// Prepare a server reply to be delivered to the client by a relay agent,
+    /// removing and returning option 82
+    /// <https://www.rfc-editor.org/rfc/rfc3046#section-2.2>
+    ///
+    /// The reply should be broadcast if the broadcast flag is set, otherwise
+    /// unicast to `yiaddr`
+    /// <https://www.rfc-editor.org/rfc/rfc1542#section-4.1.2>
+    pub fn relay_reply(&mut self) -> Option<relay::RelayAgentInformation> {
+        match self.opts.remove(OptionCode::RelayAgentInformation)? {
+            DhcpOption::RelayAgentInformation(info) => Some(info),
+            _ => unreachable!("cannot return different option for RelayAgentInformation"),
+        }
+    }
+
+    /// The parameter request list (option 55) as comma separated codes, in
+    /// the order the client sent them, i.e. `"1,3,6,15"`. This is the
+    /// fingerprint format used by device databases like Fingerbank, no
+    /// database is included.
+    ///
+    /// ```rust
+    /// use dhcproto::v4::{DhcpOption, Message, OptionCode};
+    ///
+    /// let mut msg = Message::default();
+    /// assert_eq!(msg.fingerprint(), None);
+    /// msg.opts_mut().insert(DhcpOption::ParameterRequestList(vec![
+    ///     OptionCode::SubnetMask,
+    ///     OptionCode::Router,
+    ///     OptionCode::DomainNameServer,
+    /// ]));
+    /// assert_eq!(msg.fingerprint().as_deref(), Some("1,3,6"));
+    /// ```
+    pub fn fingerprint(&self) -> Option<String> {
+        match self.opts.get(OptionCode::ParameterRequestList)? {
+            DhcpOption::ParameterRequestList(codes) => Some(
+                codes
+                    .iter()
+                    .map(|code| u8::from(*code).to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            _ => None,
+        }
+    }
+
+    /// encode the message & format it as a Wireshark style hex dump, see
+    /// [`util::hex_dump`]
+    pub fn to_hex_dump(&self) -> EncodeResult<String> {
+        Ok(util::hex_dump(&self.to_vec()?))
+    }
 }
 
 impl Decodable for Message {
@@ -491,6 +786,25 @@ impl Decodable for Message {
 
 impl Encodable for Message {
     fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        // with limits set, check the options before writing anything
+        let opts = if e.options_limit().is_some() || e.max_size().is_some() {
+            let opts = self.opts.to_vec()?;
+            if let Some(limit) = e.options_limit().filter(|limit| opts.len() > *limit) {
+                return Err(EncodeError::OptionsTooLong {
+                    len: opts.len(),
+                    limit,
+                });
+            }
+            if let Some(limit) = e
+                .max_size()
+                .filter(|limit| e.offset() + raw::HEADER_LEN + opts.len() > *limit)
+            {
+                return Err(EncodeError::MessageTooLarge { limit });
+            }
+            Some(opts)
+        } else {
+            None
+        };
         self.opcode.encode(e)?;
         self.htype.encode(e)?;
         e.write_u8(self.hlen)?;
@@ -498,16 +812,19 @@ impl Encodable for Message {
         e.write_u32(self.xid)?;
         e.write_u16(self.secs)?;
         e.write_u16(self.flags.into())?;
-        e.write_u32(self.ciaddr.into())?;
-        e.write_u32(self.yiaddr.into())?;
-        e.write_u32(self.siaddr.into())?;
-        e.write_u32(self.giaddr.into())?;
+        e.write_ipv4(self.ciaddr)?;
+        e.write_ipv4(self.yiaddr)?;
+        e.write_ipv4(self.siaddr)?;
+        e.write_ipv4(self.giaddr)?;
         e.write_slice(&self.chaddr[..])?;
         e.write_fill(&self.sname, 64)?;
         e.write_fill(&self.fname, 128)?;
 
         e.write(self.magic)?;
-        self.opts.encode(e)?;
+        match opts {
+            Some(opts) => e.write_slice(&opts)?,
+            None => self.opts.encode(e)?,
+        }
         Ok(())
     }
 }
@@ -569,6 +886,378 @@ mod tests {
         assert_eq!(msg, res);
         Ok(())
     }
+
+    #[test]
+    fn test_options_limit() -> Result<()> {
+        let mut msg = Message::default();
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Offer));
+        let mut buf = Vec::new();
+        let mut e = Encoder::new(&mut buf);
+        e.set_options_limit(Some(MAX_OPTIONS_LEN));
+        msg.encode(&mut e)?;
+        // message type + end
+        assert_eq!(buf.len(), 240 + 4);
+
+        // 2 + 255 + 2 + 45 + msg type + end = 308
+        msg.opts_mut()
+            .insert(DhcpOption::DomainName("a".repeat(300)));
+        let mut buf = Vec::new();
+        let mut e = Encoder::new(&mut buf);
+        e.set_options_limit(Some(MAX_OPTIONS_LEN));
+        msg.encode(&mut e)?;
+        assert_eq!(buf.len(), 240 + 308);
+
+        msg.opts_mut().insert(DhcpOption::Hostname("a".repeat(10)));
+        let mut buf = Vec::new();
+        let mut e = Encoder::new(&mut buf);
+        e.set_options_limit(Some(MAX_OPTIONS_LEN));
+        assert!(matches!(
+            msg.encode(&mut e),
+            Err(EncodeError::OptionsTooLong { len: 320, .. })
+        ));
+        // unlimited by default
+        assert!(msg.to_vec().is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_size() -> Result<()> {
+        let mut msg = Message::default();
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Ack));
+        msg.opts_mut()
+            .insert(DhcpOption::DomainName("a".repeat(400)));
+        let mut buf = Vec::new();
+        let mut e = Encoder::with_max_size(&mut buf, 576);
+        assert!(matches!(
+            msg.encode(&mut e),
+            Err(EncodeError::MessageTooLarge { limit: 576 })
+        ));
+        // nothing is written
+        assert!(buf.is_empty());
+
+        msg.opts_mut()
+            .insert(DhcpOption::DomainName("a".repeat(200)));
+        let mut buf = Vec::new();
+        let mut e = Encoder::with_max_size(&mut buf, 576);
+        msg.encode(&mut e)?;
+        assert!(buf.len() <= 576);
+        assert_eq!(buf.last(), Some(&u8::from(OptionCode::End)));
+        assert_eq!(Message::decode(&mut Decoder::new(&buf))?, msg);
+        Ok(())
+    }
+
+    // header & magic cookie from `discover`, followed by `opts`
+    fn with_opts(opts: &[u8]) -> Vec<u8> {
+        let mut buf = discover()[..240].to_vec();
+        buf.extend(opts);
+        buf
+    }
+
+    #[test]
+    fn test_invalid_option_value() -> Result<()> {
+        // MTU 50 ahead of the lease time & server id
+        #[rustfmt::skip]
+        let buf = with_opts(&[
+            53, 1, 5,
+            26, 2, 0, 50,
+            51, 4, 0, 0, 0x0e, 0x10,
+            54, 4, 192, 168, 0, 1,
+            255,
+        ]);
+        assert!(matches!(
+            Message::from_bytes(&buf),
+            Err(DecodeError::ValueOutOfRange {
+                code: 26,
+                value: 50
+            })
+        ));
+
+        // with a valid MTU every option is kept
+        let mut buf = buf;
+        buf[240 + 5] = 0x05;
+        buf[240 + 6] = 0xdc;
+        let msg = Message::from_bytes(&buf)?;
+        assert_eq!(msg.opts().len(), 4);
+        assert_eq!(msg.opts().lease_time(), Some(DhcpDuration::from_secs(3600)));
+
+        // an option cut short by the end of the buffer only ends the options
+        let buf = with_opts(&[53, 1, 5, 51, 4, 0, 0]);
+        assert_eq!(Message::from_bytes(&buf)?.opts().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_addr_list() {
+        for code in [3, 4, 6, 7, 8] {
+            // empty list ahead of the lease time
+            let buf = with_opts(&[53, 1, 5, code, 0, 51, 4, 0, 0, 0x0e, 0x10, 255]);
+            assert!(matches!(
+                Message::from_bytes(&buf),
+                Err(DecodeError::NotEnoughBytes)
+            ));
+        }
+        for opt in [
+            DhcpOption::Router(vec![]),
+            DhcpOption::TimeServer(vec![]),
+            DhcpOption::DomainNameServer(vec![]),
+            DhcpOption::LogServer(vec![]),
+            DhcpOption::QuoteServer(vec![]),
+        ] {
+            let code = u8::from(opt.code()).into();
+            assert!(matches!(opt.to_vec(), Err(EncodeError::EmptyList { code: c }) if c == code));
+        }
+    }
+
+    #[test]
+    fn test_msg_type_len() {
+        // 2 byte message type ahead of the lease time
+        let buf = with_opts(&[53, 2, 5, 0, 51, 4, 0, 0, 0x0e, 0x10, 255]);
+        assert!(matches!(
+            Message::from_bytes(&buf),
+            Err(DecodeError::NotEnoughBytes)
+        ));
+    }
+
+    #[test]
+    fn test_time_offset_len() {
+        // 3 byte time offset ahead of the lease time
+        let buf = with_opts(&[
+            53, 1, 5, 2, 3, 0xff, 0xb9, 0xb0, 51, 4, 0, 0, 0x0e, 0x10, 255,
+        ]);
+        assert!(matches!(
+            Message::from_bytes(&buf),
+            Err(DecodeError::NotEnoughBytes)
+        ));
+    }
+
+    #[test]
+    fn test_arp_tcp_opts_msg_len() {
+        // each with a wrong length, ahead of the lease time
+        for bad in [
+            &[35, 2, 0, 60][..],
+            &[36, 2, 0, 1],
+            &[37, 0],
+            &[38, 5, 0, 0, 0x1c, 0x20, 0],
+        ] {
+            let mut opts = vec![53, 1, 5];
+            opts.extend_from_slice(bad);
+            opts.extend_from_slice(&[51, 4, 0, 0, 0x0e, 0x10, 255]);
+            assert!(matches!(
+                Message::from_bytes(&with_opts(&opts)),
+                Err(DecodeError::NotEnoughBytes)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_renewal_msg_len() {
+        // renewal/rebinding that aren't 4 bytes, ahead of the lease time
+        for bad in [&[58, 2, 0, 30][..], &[59, 5, 0, 0, 0, 30, 0]] {
+            let mut opts = vec![53, 1, 5];
+            opts.extend_from_slice(bad);
+            opts.extend_from_slice(&[51, 4, 0, 0, 0x0e, 0x10, 255]);
+            assert!(matches!(
+                Message::from_bytes(&with_opts(&opts)),
+                Err(DecodeError::NotEnoughBytes)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_slp_mandatory_msg() {
+        // mandatory flag of 2 ahead of the lease time
+        let buf = with_opts(&[53, 1, 5, 79, 2, 2, b'a', 51, 4, 0, 0, 0x0e, 0x10, 255]);
+        assert!(matches!(
+            Message::from_bytes(&buf),
+            Err(DecodeError::ValueOutOfRange { code: 79, value: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_wpad_url_msg() {
+        // ftp wpad url ahead of the lease time
+        let mut opts = vec![53, 1, 5, 252, 19];
+        opts.extend(b"ftp://wpad/wpad.dat");
+        opts.extend([51, 4, 0, 0, 0x0e, 0x10, 255]);
+        assert!(matches!(
+            Message::from_bytes(&with_opts(&opts)),
+            Err(DecodeError::InvalidUrlScheme(scheme)) if scheme == "ftp"
+        ));
+    }
+
+    #[test]
+    fn test_sname_without_nul() -> Result<()> {
+        let mut msg = Message::default();
+        msg.set_sname(&[b'a'; 64]).set_fname_str("a".repeat(128));
+        let buf = msg.to_vec()?;
+        let decoded = Message::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(decoded.sname(), Some(&[b'a'; 64][..]));
+        assert_eq!(decoded.fname_str(), Some(Ok(&*"a".repeat(128))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut msg = Message::default();
+        msg.set_chaddr(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(msg.validate(), Ok(()));
+
+        msg.chaddr[10] = 1;
+        assert_eq!(msg.validate(), Err(ValidationError::ChaddrNotPadded(6)));
+
+        msg.set_chaddr(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(
+            msg.validate(),
+            Err(ValidationError::HlenMismatch {
+                htype: HType::Eth,
+                hlen: 8,
+                expected: 6
+            })
+        );
+        // unknown types can use any length
+        msg.set_htype(HType::Unknown(200));
+        assert_eq!(msg.validate(), Ok(()));
+
+        msg.hlen = 17;
+        assert_eq!(msg.validate(), Err(ValidationError::HlenTooLong(17)));
+    }
+
+    #[test]
+    fn test_validate_timers() {
+        let mut msg = Message::default();
+        msg.set_chaddr(&[1, 2, 3, 4, 5, 6]);
+        msg.opts_mut().insert(DhcpOption::Renewal(1800));
+        msg.opts_mut().insert(DhcpOption::Rebinding(3150));
+        // no lease time, nothing to compare against
+        assert_eq!(msg.validate(), Ok(()));
+        msg.opts_mut().insert(DhcpOption::AddressLeaseTime(3600));
+        assert_eq!(msg.validate(), Ok(()));
+
+        msg.opts_mut().insert(DhcpOption::Rebinding(1800));
+        assert!(matches!(
+            msg.validate(),
+            Err(ValidationError::Timers(
+                RenewalTimeError::RenewalAfterRebinding { .. }
+            ))
+        ));
+        msg.opts_mut().insert(DhcpOption::Rebinding(3600));
+        assert!(matches!(
+            msg.validate(),
+            Err(ValidationError::Timers(
+                RenewalTimeError::RebindingAfterLease { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_validate_requested_ip() {
+        let ip = Ipv4Addr::new(192, 168, 0, 10);
+        let mut msg = Message::default();
+        msg.set_chaddr(&[1, 2, 3, 4, 5, 6]);
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Request));
+        // SELECTING/INIT-REBOOT
+        msg.opts_mut().insert(DhcpOption::RequestedIpAddress(ip));
+        assert_eq!(msg.validate(), Ok(()));
+        // RENEWING/REBINDING
+        msg.set_ciaddr(ip);
+        assert_eq!(msg.validate(), Err(ValidationError::RequestedIpWithCiaddr));
+        msg.opts_mut().remove(OptionCode::RequestedIpAddress);
+        assert_eq!(msg.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_server_id() -> Result<()> {
+        let mut discover = Message::default();
+        discover.set_chaddr(&[1, 2, 3, 4, 5, 6]);
+        discover
+            .opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Discover));
+        assert_eq!(discover.validate(), Ok(()));
+        let server_id = Ipv4Addr::new(192, 168, 0, 1);
+        let mut offer = Message::build_offer(
+            &discover,
+            [192, 168, 0, 10].into(),
+            server_id,
+            DhcpDuration::from_secs(60),
+        )?;
+        assert_eq!(offer.validate(), Ok(()));
+        offer.opts_mut().remove(OptionCode::AddressLeaseTime);
+        assert_eq!(offer.validate(), Err(ValidationError::MissingLeaseTime));
+        offer.opts_mut().remove(OptionCode::ServerIdentifier);
+        assert_eq!(
+            offer.validate(),
+            Err(ValidationError::MissingServerIdentifier(MessageType::Offer))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_subnet() {
+        let mut msg = Message::default();
+        msg.set_chaddr(&[1, 2, 3, 4, 5, 6]);
+        msg.opts_mut()
+            .insert(DhcpOption::SubnetMask([255, 0, 255, 0].into()));
+        assert_eq!(
+            msg.validate(),
+            Err(ValidationError::Netmask(NetmaskError::NonContiguous(
+                [255, 0, 255, 0].into()
+            )))
+        );
+        msg.opts_mut()
+            .insert(DhcpOption::SubnetMask([255, 255, 255, 0].into()));
+        msg.opts_mut()
+            .insert(DhcpOption::Router(vec![[192, 168, 1, 1].into()]));
+        // no client address to compare
+        assert_eq!(msg.validate(), Ok(()));
+        msg.set_yiaddr([192, 168, 0, 10]);
+        assert_eq!(
+            msg.validate(),
+            Err(ValidationError::RouterOutsideSubnet(
+                [192, 168, 1, 1].into()
+            ))
+        );
+        msg.opts_mut()
+            .insert(DhcpOption::Router(vec![[192, 168, 0, 1].into()]));
+        assert_eq!(msg.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_relay_forward_reply() -> Result<()> {
+        let relay_addr = Ipv4Addr::new(10, 0, 0, 1);
+        let mut info = relay::RelayAgentInformation::default();
+        info.insert(relay::RelayInfo::AgentCircuitId(vec![1, 2, 3]));
+
+        let mut msg = Message::default();
+        msg.set_flags(Flags::default().set_broadcast())
+            .opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Discover));
+        msg.relay_forward(relay_addr, info.clone())?;
+        assert_eq!(msg.hops(), 1);
+        assert_eq!(msg.giaddr(), relay_addr);
+        assert!(msg.flags().broadcast());
+
+        // a second relay keeps giaddr & option 82
+        msg.relay_forward(Ipv4Addr::new(10, 0, 1, 1), Default::default())?;
+        assert_eq!(msg.hops(), 2);
+        assert_eq!(msg.giaddr(), relay_addr);
+
+        assert_eq!(msg.relay_reply(), Some(info));
+        assert!(msg.opts().get(OptionCode::RelayAgentInformation).is_none());
+        assert_eq!(msg.relay_reply(), None);
+
+        msg.set_hops(u8::MAX);
+        assert_eq!(
+            msg.relay_forward(relay_addr, Default::default())
+                .unwrap_err(),
+            RelayError::HopsOverflow
+        );
+        assert_eq!(msg.hops(), u8::MAX);
+        Ok(())
+    }
+
     #[test]
     fn decode_offer() -> Result<()> {
         decode_ipv4(offer(), MessageType::Offer)?;
@@ -0,0 +1,164 @@
+//! Microsoft Windows vendor extensions (option 43)
+//!
+//! Windows clients send a class identifier (option 60) starting with `MSFT`,
+//! i.e. `MSFT 5.0`, and accept the "Microsoft Windows 2000 Options" vendor
+//! class sub-options in option 43.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    encoder::{Encodable, Encoder},
+    error::{DecodeError, DecodeResult, EncodeResult},
+    sub_option::{SubOptionCodec, U8Tlv},
+    v4::{DhcpOption, Message, OptionCode},
+};
+
+const DISABLE_NETBIOS: u8 = 1;
+const RELEASE_ON_SHUTDOWN: u8 = 2;
+const DEFAULT_ROUTER_METRIC_BASE: u8 = 3;
+
+/// Microsoft sub-options of option 43, each carried as a 4 byte value.
+/// Unknown sub-options are ignored when decoding.
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use dhcproto::v4::{msft::MsftOption43, DhcpOption, Message};
+///
+/// let mut msg = Message::default();
+/// msg.opts_mut()
+///     .insert(DhcpOption::ClassIdentifier(b"MSFT 5.0".to_vec()));
+/// let msft = MsftOption43 {
+///     disable_netbios: Some(true),
+///     release_on_shutdown: None,
+///     default_router_metric_base: Some(10),
+/// };
+/// msg.opts_mut().insert(msft.to_option()?);
+///
+/// assert_eq!(MsftOption43::from_message(&msg)?, Some(msft));
+/// # Ok(()) }
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct MsftOption43 {
+    /// 1 - disable NetBIOS over TCP/IP
+    pub disable_netbios: Option<bool>,
+    /// 2 - release the lease when the OS shuts down
+    pub release_on_shutdown: Option<bool>,
+    /// 3 - default router metric base
+    pub default_router_metric_base: Option<u32>,
+}
+
+impl MsftOption43 {
+    /// decode option 43 if the message's class identifier (option 60)
+    /// starts with `MSFT`. Returns `None` if either option is missing or
+    /// the client isn't a Windows client.
+    pub fn from_message(msg: &Message) -> DecodeResult<Option<Self>> {
+        match (
+            msg.opts().get(OptionCode::ClassIdentifier),
+            msg.opts().get(OptionCode::VendorExtensions),
+        ) {
+            (
+                Some(DhcpOption::ClassIdentifier(class)),
+                Some(DhcpOption::VendorExtensions(data)),
+            ) if is_msft(class) => Self::from_bytes(data).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// encode as a [`DhcpOption::VendorExtensions`]
+    pub fn to_option(&self) -> EncodeResult<DhcpOption> {
+        self.to_vec().map(DhcpOption::VendorExtensions)
+    }
+}
+
+/// true if a class identifier (option 60) belongs to a Microsoft client
+pub fn is_msft(class: &[u8]) -> bool {
+    class.starts_with(b"MSFT")
+}
+
+fn read_u32(data: &[u8]) -> DecodeResult<u32> {
+    <[u8; 4]>::try_from(data)
+        .map(u32::from_be_bytes)
+        .map_err(|_| DecodeError::NotEnoughBytes)
+}
+
+impl Decodable for MsftOption43 {
+    fn decode(d: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let mut opt = MsftOption43::default();
+        for (code, data) in U8Tlv::decode(d)? {
+            match code {
+                DISABLE_NETBIOS => opt.disable_netbios = Some(read_u32(&data)? != 0),
+                RELEASE_ON_SHUTDOWN => opt.release_on_shutdown = Some(read_u32(&data)? != 0),
+                DEFAULT_ROUTER_METRIC_BASE => {
+                    opt.default_router_metric_base = Some(read_u32(&data)?)
+                }
+                _ => {}
+            }
+        }
+        Ok(opt)
+    }
+}
+
+impl Encodable for MsftOption43 {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        let netbios = self.disable_netbios.map(|b| u32::from(b).to_be_bytes());
+        let release = self.release_on_shutdown.map(|b| u32::from(b).to_be_bytes());
+        let metric = self.default_router_metric_base.map(u32::to_be_bytes);
+        let opts = [
+            netbios.as_ref().map(|v| (DISABLE_NETBIOS, &v[..])),
+            release.as_ref().map(|v| (RELEASE_ON_SHUTDOWN, &v[..])),
+            metric
+                .as_ref()
+                .map(|v| (DEFAULT_ROUTER_METRIC_BASE, &v[..])),
+        ];
+        U8Tlv::encode(&opts.into_iter().flatten().collect::<Vec<_>>(), e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_msft() -> DecodeResult<()> {
+        let buf = [
+            1, 4, 0, 0, 0, 1, // disable netbios
+            99, 1, 0, // unknown
+            2, 4, 0, 0, 0, 0, // release on shutdown
+            3, 4, 0, 0, 0, 10, // router metric base
+        ];
+        let opt = MsftOption43::from_bytes(&buf)?;
+        assert_eq!(
+            opt,
+            MsftOption43 {
+                disable_netbios: Some(true),
+                release_on_shutdown: Some(false),
+                default_router_metric_base: Some(10),
+            }
+        );
+        // unknown sub-option isn't kept
+        assert_eq!(opt.to_vec().unwrap(), [&buf[..6], &buf[9..]].concat());
+        // bad value length
+        assert!(MsftOption43::from_bytes(&[1, 1, 1]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn from_message() -> DecodeResult<()> {
+        let mut msg = Message::default();
+        msg.opts_mut()
+            .insert(DhcpOption::VendorExtensions(vec![3, 4, 0, 0, 0, 5]));
+        assert_eq!(MsftOption43::from_message(&msg)?, None);
+        msg.opts_mut()
+            .insert(DhcpOption::ClassIdentifier(b"docsis3.0".to_vec()));
+        assert_eq!(MsftOption43::from_message(&msg)?, None);
+        msg.opts_mut()
+            .insert(DhcpOption::ClassIdentifier(b"MSFT 5.0".to_vec()));
+        assert_eq!(
+            MsftOption43::from_message(&msg)?.and_then(|opt| opt.default_router_metric_base),
+            Some(5)
+        );
+        Ok(())
+    }
+}
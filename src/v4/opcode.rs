@@ -6,7 +6,11 @@ use crate::{
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-/// Opcode of Message
+/// Opcode of Message (the `op` field)
+///
+/// Values other than `BootRequest`/`BootReply` decode to `Unknown` rather
+/// than failing, like the other enums in this crate, so a message can still
+/// be inspected (or relayed) as-is. Check for `Unknown` to reject them.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Opcode {
@@ -1,9 +1,10 @@
-use std::{borrow::Cow, collections::HashMap, iter, net::Ipv4Addr};
+use std::{borrow::Cow, collections::HashMap, fmt, iter, net::Ipv4Addr};
 
 use crate::{
-    decoder::{Decodable, Decoder},
-    encoder::{Encodable, Encoder},
-    error::{DecodeResult, EncodeResult},
+    decoder::{check_url_scheme, Decodable, Decoder},
+    duration::DhcpDuration,
+    encoder::{self, Encodable, Encoder},
+    error::{DecodeError, DecodeResult, EncodeError, EncodeResult},
     v4::bulk_query,
     v4::{fqdn, relay},
 };
@@ -34,7 +35,7 @@ dhcproto_macros::declare_codes!(
     {1,   SubnetMask, "Subnet Mask", (Ipv4Addr)},
     {2,   TimeOffset, "Time Offset", (i32)},
     {3,   Router, "Router", (Vec<Ipv4Addr>)},
-    {4,   TimeServer, "Router", (Vec<Ipv4Addr>)},
+    {4,   TimeServer, "Time Server", (Vec<Ipv4Addr>)},
     {5,   NameServer, "Name Server", (Vec<Ipv4Addr>)},
     {6,   DomainNameServer, "Name Server", (Vec<Ipv4Addr>)},
     {7,   LogServer, "Log Server", (Vec<Ipv4Addr>)},
@@ -109,9 +110,14 @@ dhcproto_macros::declare_codes!(
     {76,  StreetTalkDirectoryAssistance, "StreetTalk Directory Insistance (STDA) Option", (Vec<Ipv4Addr>)},
     // TODO: split user-class into individual classes [len | <class>, ...]
     {77,  UserClass, "User Class Option - <https://www.rfc-editor.org/rfc/rfc3004.html>", (Vec<u8>)},
+    {78,  SlpDirectoryAgent, "SLP Directory Agent, mandatory flag & agent addresses - <https://www.rfc-editor.org/rfc/rfc2610.html#section-3>", (bool, Vec<Ipv4Addr>)},
+    {79,  SlpServiceScope, "SLP Service Scope, mandatory flag & scope list - <https://www.rfc-editor.org/rfc/rfc2610.html#section-4>", (bool, String)},
     {80,  RapidCommit, "Rapid Commit - <https://www.rfc-editor.org/rfc/rfc4039.html>"},
     {81,  ClientFQDN, "FQDN - <https://datatracker.ietf.org/doc/html/rfc4702>", (fqdn::ClientFQDN)},
     {82,  RelayAgentInformation, "Relay Agent Information - <https://datatracker.ietf.org/doc/html/rfc3046>", (relay::RelayAgentInformation)},
+    {85,  NdsServers, "NDS Servers - <https://www.rfc-editor.org/rfc/rfc2241.html#section-2>", (Vec<Ipv4Addr>)},
+    {86,  NdsTreeName, "NDS Tree Name - <https://www.rfc-editor.org/rfc/rfc2241.html#section-3>", (String)},
+    {87,  NdsContext, "NDS Context - <https://www.rfc-editor.org/rfc/rfc2241.html#section-4>", (String)},
     {88,  BcmsControllerNames, "Broadcast Multicast Controller Names - <https://www.rfc-editor.org/rfc/rfc4280.html#section-4.1>", (Vec<Name>)},
     {89,  BcmsControllerAddrs, "Broadcast Mutlicast Controller Address - <https://www.rfc-editor.org/rfc/rfc4280.html#section-4.3>", (Vec<Ipv4Addr>)},
     {91,  ClientLastTransactionTime, "client-last-transaction-time - <https://www.rfc-editor.org/rfc/rfc4388.html#section-6.1>", (u32)},
@@ -119,10 +125,17 @@ dhcproto_macros::declare_codes!(
     {93,  ClientSystemArchitecture, "Client System Architecture - <https://www.rfc-editor.org/rfc/rfc4578.html>", (Architecture)},
     {94,  ClientNetworkInterface, "Client Network Interface - <https://www.rfc-editor.org/rfc/rfc4578.html>", (u8, u8, u8)},
     {97,  ClientMachineIdentifier, "Client Machine Identifier - <https://www.rfc-editor.org/rfc/rfc4578.html>", (Vec<u8>)},
+    {100, PCode, "IEEE 1003.1 TZ string - <https://www.rfc-editor.org/rfc/rfc4833.html>", (String)},
+    {101, TCode, "TZ database timezone name - <https://www.rfc-editor.org/rfc/rfc4833.html>", (String)},
+    {108, Ipv6OnlyPreferred, "IPv6-Only Preferred, seconds to wait before trying v4 again - <https://www.rfc-editor.org/rfc/rfc8925.html>", (u32)},
     {114, CaptivePortal, "Captive Portal - <https://datatracker.ietf.org/doc/html/rfc8910>", (url::Url)},
+    {116, AutoConfig, "Auto-Configure, 0 is DoNotAutoConfigure & 1 AutoConfigure - <https://www.rfc-editor.org/rfc/rfc2563.html>", (u8)},
     {118, SubnetSelection, "Subnet selection - <https://datatracker.ietf.org/doc/html/rfc3011>", (Ipv4Addr)},
     {119, DomainSearch, "Domain Search - <https://www.rfc-editor.org/rfc/rfc3397.html>", (Vec<Name>)},
     {121, ClasslessStaticRoute, "Classless Static Route - <https://www.rfc-editor.org/rfc/rfc3442>", (Vec<(Ipv4Net, Ipv4Addr)>)},
+    // TODO: split into (enterprise number, data) pairs
+    {124, ViVendorClass, "Vendor-Identifying Vendor Class - <https://www.rfc-editor.org/rfc/rfc3925.html#section-3>", (Vec<u8>)},
+    {125, ViVendorSpecificInfo, "Vendor-Identifying Vendor-Specific Information - <https://www.rfc-editor.org/rfc/rfc3925.html#section-4>", (Vec<u8>)},
     {150, TFTPServerAddress, "TFTP Server Address - <https://www.rfc-editor.org/rfc/rfc5859.html>", (Ipv4Addr)},
     {151, BulkLeaseQueryStatusCode, "BLQ status-code - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.2>", (bulk_query::Code, String)},
     {152, BulkLeaseQueryBaseTime, "BLQ base time - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.3>", (u32)},
@@ -131,8 +144,36 @@ dhcproto_macros::declare_codes!(
     {155, BulkLeaseQueryQueryEndTime, "BLQ query end time- <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.6>", (u32)},
     {156, BulkLeaseQueryDhcpState, "BLQ DHCP state - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.7>", (bulk_query::QueryState)},
     {157, BulkLeaseQueryDataSource, "BLQ data source - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.8>", (bulk_query::DataSourceFlags)},
+    {161, MudUrl, "Manufacturer Usage Description URL - <https://www.rfc-editor.org/rfc/rfc8520.html#section-10>", (String)},
+    {252, WpadUrl, "Web Proxy Auto-Discovery URL, used by Microsoft clients", (String)},
     {255, End, "end-of-list marker"}
 );
+
+/// variant name of option `code` (see [`OptionCode::name`]), `None` if it's unknown
+///
+/// ```rust
+/// use dhcproto::v4::option_code_name;
+///
+/// assert_eq!(option_code_name(3), Some("Router"));
+/// // the IANA registry calls this "Domain Server"
+/// assert_eq!(option_code_name(6), Some("DomainNameServer"));
+/// assert_eq!(option_code_name(255), Some("End"));
+/// assert_eq!(option_code_name(254), None);
+/// ```
+pub fn option_code_name(code: u8) -> Option<&'static str> {
+    OptionCode::from(code).name()
+}
+
+// prints the variant name, i.e. "Router", or "Unknown(code)"
+impl fmt::Display for OptionCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "Unknown({})", u8::from(*self)),
+        }
+    }
+}
+
 /// ex
 /// ```rust
 /// use dhcproto::v4;
@@ -162,7 +203,8 @@ impl DhcpOptions {
     pub fn new() -> Self {
         Self::default()
     }
-    /// Get the data for a particular [`OptionCode`]
+    /// Get the data for a particular [`OptionCode`]. Options are stored
+    /// keyed by their code, so lookups don't scan the option list.
     ///
     /// [`OptionCode`]: crate::v4::OptionCode
     pub fn get(&self, code: OptionCode) -> Option<&DhcpOption> {
@@ -231,6 +273,20 @@ impl DhcpOptions {
     pub fn has_msg_type(&self, opt: MessageType) -> bool {
         matches!(self.get(OptionCode::MessageType), Some(DhcpOption::MessageType(msg)) if *msg == opt)
     }
+    /// return the lease time (51), `DhcpDuration::INFINITY` is a permanent
+    /// lease
+    /// ```
+    /// # use dhcproto::{v4::{DhcpOption, DhcpOptions}, DhcpDuration};
+    /// let mut opts = DhcpOptions::new();
+    /// opts.insert(DhcpOption::AddressLeaseTime(u32::MAX));
+    /// assert_eq!(opts.lease_time(), Some(DhcpDuration::INFINITY));
+    /// ```
+    pub fn lease_time(&self) -> Option<DhcpDuration> {
+        match self.get(OptionCode::AddressLeaseTime)? {
+            DhcpOption::AddressLeaseTime(secs) => Some(DhcpDuration::from_secs(*secs)),
+            _ => unreachable!("cannot return different option for AddressLeaseTime"),
+        }
+    }
     /// clear all options
     /// ```
     /// # use dhcproto::v4::{MessageType, DhcpOption, DhcpOptions};
@@ -253,13 +309,48 @@ impl DhcpOptions {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
-    /// Retains only the elements specified by the predicate
+    /// Retains only the elements specified by the predicate, i.e. a relay
+    /// stripping options before forwarding
+    /// ```
+    /// # use dhcproto::v4::{DhcpOption, DhcpOptions, MessageType, OptionCode, UnknownOption};
+    /// let mut opts = DhcpOptions::new();
+    /// opts.insert(DhcpOption::MessageType(MessageType::Request));
+    /// opts.insert(DhcpOption::Hostname("host".to_owned()));
+    /// opts.insert(DhcpOption::Unknown(UnknownOption::new(254.into(), vec![1])));
+    ///
+    /// // strip the hostname & any unknown options
+    /// opts.retain(|code, opt| {
+    ///     *code != OptionCode::Hostname && !matches!(opt, DhcpOption::Unknown(_))
+    /// });
+    /// assert_eq!(opts.len(), 1);
+    /// ```
     pub fn retain<F>(&mut self, pred: F)
     where
         F: FnMut(&OptionCode, &mut DhcpOption) -> bool,
     {
         self.0.retain(pred)
     }
+    /// Returns a copy of the options without `code`, leaving `self`
+    /// untouched, i.e. a relay stripping option 82 from a shared reply
+    /// ```
+    /// # use dhcproto::v4::{DhcpOption, DhcpOptions, MessageType, OptionCode};
+    /// let mut opts = DhcpOptions::new();
+    /// opts.insert(DhcpOption::MessageType(MessageType::Ack));
+    /// opts.insert(DhcpOption::RelayAgentInformation(Default::default()));
+    ///
+    /// let stripped = opts.clone_with_stripped_option(OptionCode::RelayAgentInformation);
+    /// assert_eq!(stripped.len(), 1);
+    /// assert_eq!(opts.len(), 2);
+    /// ```
+    pub fn clone_with_stripped_option(&self, code: OptionCode) -> Self {
+        Self(
+            self.0
+                .iter()
+                .filter(|(c, _)| **c != code)
+                .map(|(c, opt)| (*c, opt.clone()))
+                .collect(),
+        )
+    }
     /// Returns number of Options
     /// ```
     /// # use dhcproto::v4::{MessageType, DhcpOption, DhcpOptions};
@@ -301,24 +392,50 @@ impl Decodable for DhcpOptions {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         // represented as a vector in the actual message
         let mut opts = HashMap::new();
-        // should we error the whole parser if we fail to parse an
-        // option or just stop parsing options? -- here we will just stop
-        while let Ok(opt) = DhcpOption::decode(decoder) {
-            // we throw away PAD bytes here
-            match opt {
-                DhcpOption::End => {
-                    break;
-                }
-                DhcpOption::Pad => {}
-                _ => {
+        loop {
+            let start = decoder.clone();
+            match DhcpOption::decode(decoder) {
+                Ok(DhcpOption::End) => break,
+                // we throw away PAD bytes here
+                Ok(DhcpOption::Pad) => {}
+                Ok(opt) => {
                     opts.insert(OptionCode::from(&opt), opt);
                 }
+                // all of the option's bytes are here but the value is
+                // invalid, don't silently drop it & everything after it
+                Err(err) if option_complete(start.buffer()) => return Err(err),
+                // ran out of bytes or the last option is cut short, stop
+                Err(_) => break,
             }
         }
         Ok(DhcpOptions(opts))
     }
 }
 
+// true if the option at the start of `buf`, with any RFC 3396
+// continuations, has all the bytes its lengths declare
+fn option_complete(buf: &[u8]) -> bool {
+    let code = match buf.first() {
+        Some(&code) if code != u8::from(OptionCode::Pad) && code != u8::from(OptionCode::End) => {
+            code
+        }
+        _ => return false,
+    };
+    let mut rest = buf;
+    let mut seen = false;
+    while let [c, len, tail @ ..] = rest {
+        if *c != code {
+            break;
+        }
+        match tail.get(*len as usize..) {
+            Some(next) => rest = next,
+            None => return false,
+        }
+        seen = true;
+    }
+    seen
+}
+
 impl Encodable for DhcpOptions {
     fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
         if self.0.is_empty() {
@@ -357,6 +474,19 @@ impl Ord for OptionCode {
     }
 }
 
+impl DhcpOption {
+    /// the option's code, same as `OptionCode::from(&opt)`
+    /// ```
+    /// # use dhcproto::v4::{DhcpOption, OptionCode};
+    /// let opt = DhcpOption::Hostname("foo".to_owned());
+    /// assert_eq!(opt.code(), OptionCode::Hostname);
+    /// assert_eq!(u8::from(opt.code()), 12);
+    /// ```
+    pub fn code(&self) -> OptionCode {
+        OptionCode::from(self)
+    }
+}
+
 impl PartialOrd for DhcpOption {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -478,7 +608,7 @@ impl From<NodeType> for u8 {
 }
 
 #[inline]
-fn decode_inner(
+pub(crate) fn decode_inner(
     code: OptionCode,
     len: usize,
     decoder: &mut Decoder<'_>,
@@ -487,7 +617,19 @@ fn decode_inner(
     Ok(match code {
         OptionCode::Pad => Pad,
         OptionCode::SubnetMask => SubnetMask(decoder.read_ipv4(len)?),
+        // signed seconds east of UTC
+        OptionCode::TimeOffset if len != 4 => return Err(DecodeError::NotEnoughBytes),
         OptionCode::TimeOffset => TimeOffset(decoder.read_i32()?),
+        // at least one address <https://www.rfc-editor.org/rfc/rfc2132#section-3.5>
+        OptionCode::Router
+        | OptionCode::TimeServer
+        | OptionCode::DomainNameServer
+        | OptionCode::LogServer
+        | OptionCode::QuoteServer
+            if len == 0 =>
+        {
+            return Err(DecodeError::NotEnoughBytes);
+        }
         OptionCode::Router => Router(decoder.read_ipv4s(len)?),
         OptionCode::TimeServer => TimeServer(decoder.read_ipv4s(len)?),
         OptionCode::NameServer => NameServer(decoder.read_ipv4s(len)?),
@@ -500,7 +642,11 @@ fn decode_inner(
         OptionCode::Hostname => Hostname(decoder.read_string(len)?),
         OptionCode::BootFileSize => BootFileSize(decoder.read_u16()?),
         OptionCode::MeritDumpFile => MeritDumpFile(decoder.read_string(len)?),
-        OptionCode::DomainName => DomainName(decoder.read_string(len)?),
+        OptionCode::DomainName => {
+            // some servers include a nul terminator
+            let s = decoder.read_str(len)?.trim_end_matches('\0');
+            DomainName(s.to_owned())
+        }
         OptionCode::SwapServer => SwapServer(decoder.read_ipv4(len)?),
         OptionCode::RootPath => RootPath(decoder.read_string(len)?),
         OptionCode::ExtensionsPath => ExtensionsPath(decoder.read_string(len)?),
@@ -517,7 +663,16 @@ fn decode_inner(
                 .map(|num| u16::from_be_bytes([num[0], num[1]]))
                 .collect()
         }),
-        OptionCode::InterfaceMtu => InterfaceMtu(decoder.read_u16()?),
+        OptionCode::InterfaceMtu => {
+            let mtu = decoder.read_u16()?;
+            if mtu < super::MIN_INTERFACE_MTU {
+                return Err(DecodeError::ValueOutOfRange {
+                    code: u8::from(code).into(),
+                    value: mtu.into(),
+                });
+            }
+            InterfaceMtu(mtu)
+        }
         OptionCode::AllSubnetsLocal => AllSubnetsLocal(decoder.read_bool()?),
         OptionCode::BroadcastAddr => BroadcastAddr(decoder.read_ipv4(len)?),
         OptionCode::PerformMaskDiscovery => PerformMaskDiscovery(decoder.read_bool()?),
@@ -526,9 +681,15 @@ fn decode_inner(
         OptionCode::RouterSolicitationAddr => RouterSolicitationAddr(decoder.read_ipv4(len)?),
         OptionCode::StaticRoutingTable => StaticRoutingTable(decoder.read_pair_ipv4s(len)?),
         OptionCode::TrailerEncapsulated => TrailerEncapsulated(decoder.read_bool()?),
+        OptionCode::ArpCacheTimeout | OptionCode::TcpKeepaliveInterval if len != 4 => {
+            return Err(DecodeError::NotEnoughBytes);
+        }
+        OptionCode::EthernetEncapsulation | OptionCode::DefaultTcpTtl if len != 1 => {
+            return Err(DecodeError::NotEnoughBytes);
+        }
         OptionCode::ArpCacheTimeout => ArpCacheTimeout(decoder.read_u32()?),
         OptionCode::EthernetEncapsulation => EthernetEncapsulation(decoder.read_bool()?),
-        OptionCode::DefaultTcpTtl => DefaultIpTtl(decoder.read_u8()?),
+        OptionCode::DefaultTcpTtl => DefaultTcpTtl(decoder.read_u8()?),
         OptionCode::TcpKeepaliveInterval => TcpKeepaliveInterval(decoder.read_u32()?),
         OptionCode::TcpKeepaliveGarbage => TcpKeepaliveGarbage(decoder.read_bool()?),
         OptionCode::NisDomain => NisDomain(decoder.read_string(len)?),
@@ -546,6 +707,7 @@ fn decode_inner(
         OptionCode::RequestedIpAddress => RequestedIpAddress(decoder.read_ipv4(len)?),
         OptionCode::AddressLeaseTime => AddressLeaseTime(decoder.read_u32()?),
         OptionCode::OptionOverload => OptionOverload(decoder.read_u8()?),
+        OptionCode::MessageType if len != 1 => return Err(DecodeError::NotEnoughBytes),
         OptionCode::MessageType => MessageType(decoder.read_u8()?.into()),
         OptionCode::ServerIdentifier => ServerIdentifier(decoder.read_ipv4(len)?),
         OptionCode::ParameterRequestList => ParameterRequestList(
@@ -557,13 +719,20 @@ fn decode_inner(
         ),
         OptionCode::Message => Message(decoder.read_string(len)?),
         OptionCode::MaxMessageSize => MaxMessageSize(decoder.read_u16()?),
+        OptionCode::Renewal | OptionCode::Rebinding if len != 4 => {
+            return Err(DecodeError::NotEnoughBytes);
+        }
         OptionCode::Renewal => Renewal(decoder.read_u32()?),
         OptionCode::Rebinding => Rebinding(decoder.read_u32()?),
         OptionCode::ClassIdentifier => ClassIdentifier(decoder.read_slice(len)?.to_vec()),
         OptionCode::ClientIdentifier => ClientIdentifier(decoder.read_slice(len)?.to_vec()),
         OptionCode::NwipDomainName => NwipDomainName(decoder.read_string(len)?),
         OptionCode::NwipInformation => NwipInformation(decoder.read_slice(len)?.to_vec()),
-        OptionCode::NispServiceDomain => NispServiceDomain(decoder.read_string(len)?),
+        OptionCode::NispServiceDomain => {
+            // some servers include a nul terminator
+            let s = decoder.read_str(len)?.trim_end_matches('\0');
+            NispServiceDomain(s.to_owned())
+        }
         OptionCode::NispServers => NispServers(decoder.read_ipv4s(len)?),
         OptionCode::TFTPServerName => TFTPServerName(decoder.read_slice(len)?.to_vec()),
         OptionCode::BootfileName => BootfileName(decoder.read_slice(len)?.to_vec()),
@@ -579,15 +748,35 @@ fn decode_inner(
             StreetTalkDirectoryAssistance(decoder.read_ipv4s(len)?)
         }
         OptionCode::UserClass => UserClass(decoder.read_slice(len)?.to_vec()),
-
-        OptionCode::RapidCommit => {
-            debug_assert!(len == 0);
-            RapidCommit
+        OptionCode::SlpDirectoryAgent | OptionCode::SlpServiceScope => {
+            let mandatory = match decoder.read_u8()? {
+                0 => false,
+                1 => true,
+                value => {
+                    return Err(DecodeError::ValueOutOfRange {
+                        code: u8::from(code).into(),
+                        value: value.into(),
+                    })
+                }
+            };
+            // len - 1 because the flag is included in length
+            let len = len.checked_sub(1).ok_or(DecodeError::NotEnoughBytes)?;
+            if code == OptionCode::SlpDirectoryAgent {
+                SlpDirectoryAgent(mandatory, decoder.read_ipv4s(len)?)
+            } else {
+                SlpServiceScope(mandatory, decoder.read_string(len)?)
+            }
         }
+
+        OptionCode::RapidCommit if len != 0 => return Err(DecodeError::NotEnoughBytes),
+        OptionCode::RapidCommit => RapidCommit,
         OptionCode::RelayAgentInformation => {
             let mut dec = Decoder::new(decoder.read_slice(len)?);
             RelayAgentInformation(relay::RelayAgentInformation::decode(&mut dec)?)
         }
+        OptionCode::NdsServers => NdsServers(decoder.read_ipv4s(len)?),
+        OptionCode::NdsTreeName => NdsTreeName(decoder.read_string(len)?),
+        OptionCode::NdsContext => NdsContext(decoder.read_string(len)?),
         OptionCode::BcmsControllerNames => BcmsControllerNames(decoder.read_domains(len)?),
         OptionCode::BcmsControllerAddrs => BcmsControllerAddrs(decoder.read_ipv4s(len)?),
         OptionCode::ClientLastTransactionTime => ClientLastTransactionTime(decoder.read_u32()?),
@@ -596,51 +785,79 @@ fn decode_inner(
             let ty = decoder.read_u16()?;
             ClientSystemArchitecture(ty.into())
         }
+        OptionCode::ClientNetworkInterface if len != 3 => {
+            return Err(DecodeError::NotEnoughBytes);
+        }
         OptionCode::ClientNetworkInterface => {
-            debug_assert!(len == 3);
             ClientNetworkInterface(decoder.read_u8()?, decoder.read_u8()?, decoder.read_u8()?)
         }
         OptionCode::ClientMachineIdentifier => {
             ClientMachineIdentifier(decoder.read_slice(len)?.to_vec())
         }
+        OptionCode::PCode => PCode(decoder.read_string(len)?),
+        OptionCode::TCode => TCode(decoder.read_string(len)?),
+        OptionCode::Ipv6OnlyPreferred if len != 4 => return Err(DecodeError::NotEnoughBytes),
+        OptionCode::Ipv6OnlyPreferred => Ipv6OnlyPreferred(decoder.read_u32()?),
         OptionCode::CaptivePortal => CaptivePortal(decoder.read_str(len)?.parse()?),
+        OptionCode::AutoConfig if len != 1 => return Err(DecodeError::NotEnoughBytes),
+        OptionCode::AutoConfig => AutoConfig(decoder.read_u8()?),
+        OptionCode::WpadUrl => {
+            // some servers include a nul terminator
+            let s = decoder.read_str(len)?.trim_end_matches('\0');
+            check_url_scheme(s, &["http", "https"])?;
+            WpadUrl(s.to_owned())
+        }
+        OptionCode::MudUrl => {
+            let s = decoder.read_string(len)?;
+            check_url_scheme(&s, &["https"])?;
+            MudUrl(s)
+        }
         OptionCode::SubnetSelection => SubnetSelection(decoder.read_ipv4(len)?),
         OptionCode::DomainSearch => DomainSearch(decoder.read_domains(len)?),
+        OptionCode::ViVendorClass => ViVendorClass(decoder.read_slice(len)?.to_vec()),
+        OptionCode::ViVendorSpecificInfo => ViVendorSpecificInfo(decoder.read_slice(len)?.to_vec()),
         OptionCode::TFTPServerAddress => TFTPServerAddress(decoder.read_ipv4(len)?),
         OptionCode::BulkLeaseQueryStatusCode => {
             let code = decoder.read_u8()?.into();
             // len - 1 because code is included in length
-            let message = decoder.read_string(len - 1)?;
+            let len = len.checked_sub(1).ok_or(DecodeError::NotEnoughBytes)?;
+            let message = decoder.read_string(len)?;
             BulkLeaseQueryStatusCode(code, message)
         }
-        OptionCode::BulkLeaseQueryBaseTime => {
-            debug_assert!(len == 4);
-            BulkLeaseQueryBaseTime(decoder.read_u32()?)
+        OptionCode::BulkLeaseQueryBaseTime
+        | OptionCode::BulkLeasQueryStartTimeOfState
+        | OptionCode::BulkLeaseQueryQueryStartTime
+        | OptionCode::BulkLeaseQueryQueryEndTime
+            if len != 4 =>
+        {
+            return Err(DecodeError::NotEnoughBytes);
         }
+        OptionCode::BulkLeaseQueryBaseTime => BulkLeaseQueryBaseTime(decoder.read_u32()?),
         OptionCode::BulkLeasQueryStartTimeOfState => {
-            debug_assert!(len == 4);
             BulkLeasQueryStartTimeOfState(decoder.read_u32()?)
         }
         OptionCode::BulkLeaseQueryQueryStartTime => {
-            debug_assert!(len == 4);
             BulkLeaseQueryQueryStartTime(decoder.read_u32()?)
         }
-        OptionCode::BulkLeaseQueryQueryEndTime => {
-            debug_assert!(len == 4);
-            BulkLeaseQueryQueryEndTime(decoder.read_u32()?)
-        }
+        OptionCode::BulkLeaseQueryQueryEndTime => BulkLeaseQueryQueryEndTime(decoder.read_u32()?),
         OptionCode::BulkLeaseQueryDhcpState => BulkLeaseQueryDhcpState(decoder.read_u8()?.into()),
         OptionCode::BulkLeaseQueryDataSource => {
             BulkLeaseQueryDataSource(bulk_query::DataSourceFlags::new(decoder.read_u8()?))
         }
+        // flags & 2 rcodes come before the name
+        OptionCode::ClientFQDN if len < 3 => return Err(DecodeError::NotEnoughBytes),
         OptionCode::ClientFQDN => {
-            debug_assert!(len >= 3);
-            let flags = decoder.read_u8()?.into();
+            let flags: fqdn::FqdnFlags = decoder.read_u8()?.into();
             let rcode1 = decoder.read_u8()?;
             let rcode2 = decoder.read_u8()?;
 
-            let mut name_decoder = BinDecoder::new(decoder.read_slice(len - 3)?);
-            let name = Name::read(&mut name_decoder)?;
+            let name = if flags.e() {
+                let mut name_decoder = BinDecoder::new(decoder.read_slice(len - 3)?);
+                Name::read(&mut name_decoder)?
+            } else {
+                // deprecated ASCII encoding, matches what we emit in `encode`
+                Name::from_ascii(decoder.read_str(len - 3)?)?
+            };
             ClientFQDN(fqdn::ClientFQDN {
                 flags,
                 r1: rcode1,
@@ -703,6 +920,14 @@ impl Decodable for DhcpOption {
             fn decode(dec: &mut Decoder<'a>) -> DecodeResult<Self> {
                 // TODO: necessary to call u8::from_be_bytes?
                 let [code, len] = dec.peek::<2>()?;
+                let available = dec.buffer().len() - 2;
+                if available < len as usize {
+                    return Err(DecodeError::TruncatedOption {
+                        code,
+                        declared_len: len as usize,
+                        available,
+                    });
+                }
                 let buf = Cow::from(dec.read_slice(len as usize + 2)?);
                 Ok(Opt { code, buf })
             }
@@ -751,8 +976,7 @@ impl Decodable for DhcpOption {
                 }
             }
         }
-        last.ok_or(crate::error::DecodeError::NotEnoughBytes)?
-            .as_option()
+        last.ok_or(DecodeError::NotEnoughBytes)?.as_option()
     }
 }
 
@@ -816,7 +1040,7 @@ pub fn encode_long_opt_domains(
 /// let mut buf = Vec::new();
 /// let mut e = Encoder::new(&mut buf);
 /// let opt = iter::repeat(Ipv4Addr::from([1,2,3,4])).take(80).collect::<Vec<_>>();
-/// let res = encode_long_opt_chunks(OptionCode::NisServers, 4, &opt, |ip, e| e.write_u32((*ip).into()), &mut e);
+/// let res = encode_long_opt_chunks(OptionCode::NisServers, 4, &opt, |ip, e| e.write_ipv4(*ip), &mut e);
 /// // [code, 252, 1,2,3,4,1,2,3,4 ..., code, 68, 1,2,3,4, ...]
 /// let mut x = vec![OptionCode::NisServers.into(), 252];
 /// x.extend(iter::repeat(Ipv4Addr::from([1,2,3,4])).map(|ip| u32::from(ip).to_be_bytes()).flatten().take(252));
@@ -849,11 +1073,38 @@ where
     Ok(())
 }
 
+// values `decode_inner` rejects, so we don't encode an option we can't decode
+fn check_value(opt: &DhcpOption) -> EncodeResult<()> {
+    let code = u8::from(OptionCode::from(opt)).into();
+    match opt {
+        DhcpOption::InterfaceMtu(mtu) if *mtu < super::MIN_INTERFACE_MTU => {
+            Err(EncodeError::ValueOutOfRange {
+                code,
+                value: (*mtu).into(),
+            })
+        }
+        // at least one address <https://www.rfc-editor.org/rfc/rfc2132#section-3.5>
+        DhcpOption::Router(ips)
+        | DhcpOption::TimeServer(ips)
+        | DhcpOption::DomainNameServer(ips)
+        | DhcpOption::LogServer(ips)
+        | DhcpOption::QuoteServer(ips)
+            if ips.is_empty() =>
+        {
+            Err(EncodeError::EmptyList { code })
+        }
+        DhcpOption::WpadUrl(s) => encoder::check_url_scheme(s, &["http", "https"]),
+        DhcpOption::MudUrl(s) => encoder::check_url_scheme(s, &["https"]),
+        _ => Ok(()),
+    }
+}
+
 impl Encodable for DhcpOption {
     fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
         use DhcpOption as O;
 
         let code: OptionCode = self.into();
+        check_value(self)?;
         // pad has no length, so we can't read len up here.
         // don't want to have a fall-through case either
         // so we get exhaustiveness checking, so we'll parse
@@ -876,7 +1127,7 @@ impl Encodable for DhcpOption {
             | O::TFTPServerAddress(addr) => {
                 e.write_u8(code.into())?;
                 e.write_u8(4)?;
-                e.write_u32((*addr).into())?
+                e.write_ipv4(*addr)?
             }
             O::TimeOffset(offset) => {
                 e.write_u8(code.into())?;
@@ -897,6 +1148,7 @@ impl Encodable for DhcpOption {
             | O::NisServers(ips)
             | O::NtpServers(ips)
             | O::NetBiosNameServers(ips)
+            | O::NdsServers(ips)
             | O::NetBiosDatagramDistributionServer(ips)
             | O::AssociatedIp(ips)
             | O::NispServers(ips)
@@ -910,7 +1162,7 @@ impl Encodable for DhcpOption {
             | O::SmtpServer(ips)
             | O::IrcServer(ips)
             | O::BcmsControllerAddrs(ips) => {
-                encode_long_opt_chunks(code, 4, ips, |ip, e| e.write_u32((*ip).into()), e)?;
+                encode_long_opt_chunks(code, 4, ips, |ip, e| e.write_ipv4(*ip), e)?;
             }
             O::Hostname(s)
             | O::MeritDumpFile(s)
@@ -921,7 +1173,13 @@ impl Encodable for DhcpOption {
             | O::NetBiosScope(s)
             | O::Message(s)
             | O::NwipDomainName(s)
-            | O::NispServiceDomain(s) => {
+            | O::NispServiceDomain(s)
+            | O::NdsTreeName(s)
+            | O::NdsContext(s)
+            | O::PCode(s)
+            | O::TCode(s)
+            | O::WpadUrl(s)
+            | O::MudUrl(s) => {
                 encode_long_opt_bytes(code, s.as_bytes(), e)?;
             }
             O::BootFileSize(num)
@@ -945,7 +1203,10 @@ impl Encodable for DhcpOption {
                 e.write_u8(1)?;
                 e.write_u8((*b).into())?
             }
-            O::DefaultIpTtl(byte) | O::DefaultTcpTtl(byte) | O::OptionOverload(byte) => {
+            O::DefaultIpTtl(byte)
+            | O::DefaultTcpTtl(byte)
+            | O::OptionOverload(byte)
+            | O::AutoConfig(byte) => {
                 e.write_u8(code.into())?;
                 e.write_u8(1)?;
                 e.write_u8(*byte)?
@@ -955,10 +1216,7 @@ impl Encodable for DhcpOption {
                     code,
                     8,
                     pair_ips,
-                    |(a, b), e| {
-                        e.write_u32((*a).into())?;
-                        e.write_u32((*b).into())
-                    },
+                    |pair, e| e.write_pair_ipv4s(std::slice::from_ref(pair)),
                     e,
                 )?;
             }
@@ -972,6 +1230,7 @@ impl Encodable for DhcpOption {
             | O::BulkLeasQueryStartTimeOfState(num)
             | O::BulkLeaseQueryQueryStartTime(num)
             | O::BulkLeaseQueryQueryEndTime(num)
+            | O::Ipv6OnlyPreferred(num)
             | O::PathMtuAgingTimeout(num) => {
                 e.write_u8(code.into())?;
                 e.write_u8(4)?;
@@ -984,6 +1243,8 @@ impl Encodable for DhcpOption {
             | O::TFTPServerName(bytes)
             | O::BootfileName(bytes)
             | O::NwipInformation(bytes)
+            | O::ViVendorClass(bytes)
+            | O::ViVendorSpecificInfo(bytes)
             | O::UserClass(bytes) => {
                 encode_long_opt_bytes(code, bytes, e)?;
             }
@@ -1023,6 +1284,16 @@ impl Encodable for DhcpOption {
                 let url = url.to_string();
                 encode_long_opt_bytes(code, url.as_bytes(), e)?;
             }
+            O::SlpDirectoryAgent(mandatory, addrs) => {
+                let mut buf = vec![*mandatory as u8];
+                buf.extend(addrs.iter().flat_map(|ip| ip.octets()));
+                encode_long_opt_bytes(code, &buf, e)?;
+            }
+            O::SlpServiceScope(mandatory, scope) => {
+                let mut buf = vec![*mandatory as u8];
+                buf.extend(scope.as_bytes());
+                encode_long_opt_bytes(code, &buf, e)?;
+            }
             O::BulkLeaseQueryStatusCode(status_code, msg) => {
                 e.write_u8(code.into())?;
                 let msg = msg.as_bytes();
@@ -1069,7 +1340,7 @@ impl Encodable for DhcpOption {
                     let byte_len = (dest.prefix_len() + 7) / 8;
                     route_enc.write_u8(dest.prefix_len())?;
                     route_enc.write_slice(&dest.addr().octets()[0..byte_len as usize])?;
-                    route_enc.write(gw.octets())?;
+                    route_enc.write_ipv4(*gw)?;
                 }
 
                 encode_long_opt_bytes(code, &buf, e)?;
@@ -1387,12 +1658,425 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_netbios_opts() -> Result<()> {
+        // WINS servers, H-node & scope, as handed out to Windows clients
+        let buf = [
+            44, 8, 10, 0, 0, 5, 10, 0, 1, 5, // name servers
+            45, 4, 10, 0, 0, 6, // datagram distribution server
+            46, 1, 8, // node type
+            47, 4, b'c', b'o', b'r', b'p', // scope
+            255,
+        ];
+        let opts = DhcpOptions::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(
+            opts.get(OptionCode::NetBiosNameServers),
+            Some(&DhcpOption::NetBiosNameServers(vec![
+                Ipv4Addr::new(10, 0, 0, 5),
+                Ipv4Addr::new(10, 0, 1, 5)
+            ]))
+        );
+        assert_eq!(
+            opts.get(OptionCode::NetBiosDatagramDistributionServer),
+            Some(&DhcpOption::NetBiosDatagramDistributionServer(vec![
+                Ipv4Addr::new(10, 0, 0, 6)
+            ]))
+        );
+        assert_eq!(
+            opts.get(OptionCode::NetBiosNodeType),
+            Some(&DhcpOption::NetBiosNodeType(NodeType::H))
+        );
+        assert_eq!(
+            opts.get(OptionCode::NetBiosScope),
+            Some(&DhcpOption::NetBiosScope("corp".to_owned()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nis_ntp_opts() -> Result<()> {
+        let buf = [
+            40, 4, b'c', b'o', b'r', b'p', // nis domain
+            41, 4, 10, 0, 0, 2, // nis servers
+            42, 12, 10, 0, 0, 1, 10, 0, 0, 2, 10, 0, 0, 3, // ntp servers
+            255,
+        ];
+        let opts = DhcpOptions::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(
+            opts.get(OptionCode::NisDomain),
+            Some(&DhcpOption::NisDomain("corp".to_owned()))
+        );
+        assert_eq!(
+            opts.get(OptionCode::NisServers),
+            Some(&DhcpOption::NisServers(vec![Ipv4Addr::new(10, 0, 0, 2)]))
+        );
+        let ntp = DhcpOption::NtpServers(vec![
+            Ipv4Addr::new(10, 0, 0, 1),
+            Ipv4Addr::new(10, 0, 0, 2),
+            Ipv4Addr::new(10, 0, 0, 3),
+        ]);
+        assert_eq!(opts.get(OptionCode::NtpServers), Some(&ntp));
+        assert_eq!(ntp.to_vec()?, buf[12..26]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dns_servers() -> Result<()> {
+        test_opt(
+            DhcpOption::DomainNameServer(vec![Ipv4Addr::new(1, 1, 1, 1)]),
+            vec![6, 4, 1, 1, 1, 1],
+        )?;
+        test_opt(
+            DhcpOption::DomainNameServer(vec![
+                Ipv4Addr::new(1, 1, 1, 1),
+                Ipv4Addr::new(8, 8, 8, 8),
+            ]),
+            vec![6, 8, 1, 1, 1, 1, 8, 8, 8, 8],
+        )
+    }
+
+    #[test]
+    fn test_www_finger_irc_servers() -> Result<()> {
+        let ips = vec![Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)];
+        for (code, opt) in [
+            (72, DhcpOption::WwwServer(ips.clone())),
+            (73, DhcpOption::DefaultFingerServer(ips.clone())),
+            (74, DhcpOption::IrcServer(ips)),
+        ] {
+            assert_eq!(u8::from(OptionCode::from(&opt)), code);
+            let buf = [code, 8, 10, 0, 0, 1, 10, 0, 0, 2];
+            assert_eq!(DhcpOption::decode(&mut Decoder::new(&buf))?, opt);
+            assert_eq!(opt.to_vec()?, buf);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_message_opt() -> Result<()> {
+        let mut buf = vec![56, 17];
+        buf.extend(b"wrong network, 1!");
+        buf.push(255);
+        let opts = DhcpOptions::decode(&mut Decoder::new(&buf))?;
+        let msg = DhcpOption::Message("wrong network, 1!".to_owned());
+        assert_eq!(opts.get(OptionCode::Message), Some(&msg));
+        // no nul is added
+        assert_eq!(msg.to_vec()?, buf[..19]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_domain_name() -> Result<()> {
+        let mut buf = vec![15, 12];
+        buf.extend(b"example.com.");
+        let opt = DhcpOption::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(opt, DhcpOption::DomainName("example.com.".to_owned()));
+        assert_eq!(opt.to_vec()?, buf);
+        assert!(crate::dns_labels::validate_name("example.com.").is_ok());
+
+        let mut buf = vec![15, 12];
+        buf.extend(b"example.com\0");
+        assert_eq!(
+            DhcpOption::decode(&mut Decoder::new(&buf))?,
+            DhcpOption::DomainName("example.com".to_owned())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nisp_domain_nul() -> Result<()> {
+        let mut buf = vec![64, 9];
+        buf.extend(b"corp.com\0");
+        assert_eq!(
+            DhcpOption::decode(&mut Decoder::new(&buf))?,
+            DhcpOption::NispServiceDomain("corp.com".to_owned())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_slp_mandatory() -> Result<()> {
+        test_opt(
+            DhcpOption::SlpDirectoryAgent(true, vec![Ipv4Addr::new(10, 0, 0, 1)]),
+            vec![78, 5, 1, 10, 0, 0, 1],
+        )?;
+        test_opt(
+            DhcpOption::SlpServiceScope(false, "a,b".to_owned()),
+            vec![79, 4, 0, b'a', b',', b'b'],
+        )?;
+        assert!(matches!(
+            DhcpOption::decode(&mut Decoder::new(&[79, 2, 2, b'a'])),
+            Err(DecodeError::ValueOutOfRange { code: 79, value: 2 })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_offset() -> Result<()> {
+        // UTC-5
+        assert_eq!(
+            DhcpOption::decode(&mut Decoder::new(&[2, 4, 0xff, 0xff, 0xb9, 0xb0]))?,
+            DhcpOption::TimeOffset(-18000)
+        );
+        assert!(DhcpOption::decode(&mut Decoder::new(&[2, 2, 0xb9, 0xb0])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_arp_tcp_opts_len() -> Result<()> {
+        let buf = [
+            35, 4, 0, 0, 0, 60, // arp cache timeout
+            36, 1, 1, // ethernet encapsulation
+            37, 1, 64, // default tcp ttl
+            38, 4, 0, 0, 0x1c, 0x20, // tcp keepalive interval
+            255,
+        ];
+        let opts = DhcpOptions::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(
+            opts.get(OptionCode::ArpCacheTimeout),
+            Some(&DhcpOption::ArpCacheTimeout(60))
+        );
+        assert_eq!(
+            opts.get(OptionCode::EthernetEncapsulation),
+            Some(&DhcpOption::EthernetEncapsulation(true))
+        );
+        assert_eq!(
+            opts.get(OptionCode::DefaultTcpTtl),
+            Some(&DhcpOption::DefaultTcpTtl(64))
+        );
+        assert_eq!(
+            opts.get(OptionCode::TcpKeepaliveInterval),
+            Some(&DhcpOption::TcpKeepaliveInterval(7200))
+        );
+
+        for buf in [
+            [35, 2, 0, 60],
+            [36, 2, 1, 0],
+            [37, 2, 0, 64],
+            [38, 2, 0, 60],
+        ] {
+            assert!(DhcpOption::decode(&mut Decoder::new(&buf)).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_lease_time_infinity() -> Result<()> {
+        let opt = DhcpOption::AddressLeaseTime(DhcpDuration::INFINITY.as_secs());
+        let buf = [51, 4, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(opt.to_vec()?, buf);
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::decode(&mut Decoder::new(&buf))?);
+        assert!(opts.lease_time().unwrap().is_infinite());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pad_end() -> Result<()> {
+        let buf = [
+            0, 0, 0, // pad
+            1, 4, 255, 255, 255, 0, // subnet mask
+            0, 0, // pad
+            3, 4, 10, 0, 0, 1,   // router
+            255, // end
+            12, 3, b'f', b'o', b'o', // past end, ignored
+        ];
+        let opts = DhcpOptions::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(opts.len(), 2);
+        assert!(opts.get(OptionCode::SubnetMask).is_some());
+        assert!(opts.get(OptionCode::Router).is_some());
+        assert!(opts.get(OptionCode::Hostname).is_none());
+
+        // pad & end are single bytes without a length
+        let mut decoder = Decoder::new(&[0, 255]);
+        assert_eq!(DhcpOption::decode(&mut decoder)?, DhcpOption::Pad);
+        assert_eq!(DhcpOption::decode(&mut decoder)?, DhcpOption::End);
+        assert!(decoder.buffer().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_clone_with_stripped_option() {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::SubnetMask(Ipv4Addr::new(255, 255, 255, 0)));
+        opts.insert(DhcpOption::Router(vec![Ipv4Addr::new(192, 168, 0, 1)]));
+        opts.insert(DhcpOption::DomainNameServer(vec![Ipv4Addr::new(
+            1, 1, 1, 1,
+        )]));
+        opts.insert(DhcpOption::RelayAgentInformation(
+            relay::RelayAgentInformation::default(),
+        ));
+
+        let stripped = opts.clone_with_stripped_option(OptionCode::RelayAgentInformation);
+        let mut codes = stripped
+            .iter()
+            .map(|(code, _)| u8::from(*code))
+            .collect::<Vec<_>>();
+        codes.sort_unstable();
+        assert_eq!(codes, [1, 3, 6]);
+        // original is unchanged
+        assert_eq!(opts.len(), 4);
+        assert_eq!(
+            stripped.get(OptionCode::Router),
+            opts.get(OptionCode::Router)
+        );
+    }
+
+    #[test]
+    fn test_msg_type_len() -> Result<()> {
+        assert_eq!(
+            DhcpOption::decode(&mut Decoder::new(&[53, 1, 5]))?,
+            DhcpOption::MessageType(MessageType::Ack)
+        );
+        // unassigned types are kept
+        assert_eq!(
+            DhcpOption::decode(&mut Decoder::new(&[53, 1, 200]))?,
+            DhcpOption::MessageType(MessageType::Unknown(200))
+        );
+        assert!(DhcpOption::decode(&mut Decoder::new(&[53, 0])).is_err());
+        assert!(DhcpOption::decode(&mut Decoder::new(&[53, 2, 5, 0])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_option_code_display() {
+        assert_eq!(OptionCode::SubnetMask.to_string(), "SubnetMask");
+        assert_eq!(OptionCode::from(254).to_string(), "Unknown(254)");
+    }
+
+    #[test]
+    fn test_truncated_option() {
+        // hostname declares 5 bytes, 3 left
+        assert!(matches!(
+            DhcpOption::decode(&mut Decoder::new(&[12, 5, b'f', b'o', b'o'])),
+            Err(DecodeError::TruncatedOption {
+                code: 12,
+                declared_len: 5,
+                available: 3
+            })
+        ));
+        // second part of a split option
+        assert!(matches!(
+            DhcpOption::decode(&mut Decoder::new(&[12, 1, b'f', 12, 2, b'o'])),
+            Err(DecodeError::TruncatedOption {
+                code: 12,
+                declared_len: 2,
+                available: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_renewal_len() {
+        assert!(DhcpOption::decode(&mut Decoder::new(&[58, 2, 0, 30])).is_err());
+        assert!(DhcpOption::decode(&mut Decoder::new(&[59, 5, 0, 0, 0, 30, 0])).is_err());
+    }
+
+    #[test]
+    fn test_server_list_len() {
+        for code in [3, 4, 6, 7, 8] {
+            assert!(DhcpOption::decode(&mut Decoder::new(&[code, 0])).is_err());
+            assert!(DhcpOption::decode(&mut Decoder::new(&[code, 3, 10, 0, 0])).is_err());
+        }
+        assert_eq!(
+            DhcpOption::decode(&mut Decoder::new(&[7, 4, 10, 0, 0, 1])).unwrap(),
+            DhcpOption::LogServer(vec![Ipv4Addr::new(10, 0, 0, 1)])
+        );
+    }
+
+    #[test]
+    fn test_mud_url_https() {
+        let mut buf = vec![161, 22];
+        buf.extend(b"http://example.org/mud");
+        assert!(matches!(
+            DhcpOption::decode(&mut Decoder::new(&buf)),
+            Err(DecodeError::InvalidUrlScheme(scheme)) if scheme == "http"
+        ));
+        assert!(matches!(
+            DhcpOption::MudUrl("http://example.org/mud".to_owned()).to_vec(),
+            Err(EncodeError::InvalidUrlScheme(scheme)) if scheme == "http"
+        ));
+    }
+
+    #[test]
+    fn test_interface_mtu() -> Result<()> {
+        test_opt(DhcpOption::InterfaceMtu(68), vec![26, 2, 0, 68])?;
+        assert!(matches!(
+            DhcpOption::decode(&mut Decoder::new(&[26, 2, 0, 67])),
+            Err(DecodeError::ValueOutOfRange {
+                code: 26,
+                value: 67
+            })
+        ));
+        // rejected on encode too
+        assert!(matches!(
+            DhcpOption::InterfaceMtu(67).to_vec(),
+            Err(EncodeError::ValueOutOfRange {
+                code: 26,
+                value: 67
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_wpad_url() -> Result<()> {
+        // nul terminated, as sent by some servers
+        let mut buf = vec![252, 21];
+        buf.extend(b"http://wpad/wpad.dat\0");
+        assert_eq!(
+            DhcpOption::decode(&mut Decoder::new(&buf))?,
+            DhcpOption::WpadUrl("http://wpad/wpad.dat".to_owned())
+        );
+
+        let mut buf = vec![252, 19];
+        buf.extend(b"ftp://wpad/wpad.dat");
+        assert!(matches!(
+            DhcpOption::decode(&mut Decoder::new(&buf)),
+            Err(DecodeError::InvalidUrlScheme(scheme)) if scheme == "ftp"
+        ));
+        assert!(matches!(
+            DhcpOption::WpadUrl("ftp://wpad/wpad.dat".to_owned()).to_vec(),
+            Err(EncodeError::InvalidUrlScheme(scheme)) if scheme == "ftp"
+        ));
+        assert!(matches!(
+            DhcpOption::WpadUrl("wpad".to_owned()).to_vec(),
+            Err(EncodeError::UrlParseError(_))
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_rapid_commit() -> Result<()> {
         test_opt(DhcpOption::RapidCommit, vec![80, 0])?;
 
         Ok(())
     }
+
+    #[test]
+    fn test_v6_only_auto_config_len() {
+        assert!(DhcpOption::decode(&mut Decoder::new(&[108, 2, 0, 0])).is_err());
+        assert!(DhcpOption::decode(&mut Decoder::new(&[116, 0])).is_err());
+    }
+
+    #[test]
+    fn test_fixed_len_opts() {
+        // lengths that used to trip a debug_assert or underflow
+        for buf in [
+            &[80, 1, 0][..],
+            &[81, 2, 0, 0],
+            &[94, 2, 1, 0],
+            &[151, 0],
+            &[152, 2, 0, 0],
+            &[153, 5, 0, 0, 0, 0, 0],
+            &[154, 0],
+            &[155, 3, 0, 0, 0],
+        ] {
+            assert!(matches!(
+                DhcpOption::decode(&mut Decoder::new(buf)),
+                Err(DecodeError::NotEnoughBytes)
+            ));
+        }
+    }
     #[test]
     fn test_status() -> Result<()> {
         let msg = "message".to_string();
@@ -1539,4 +2223,306 @@ mod tests {
             8,
         )
     }
+
+    // every variant should survive an encode/decode roundtrip
+    mod roundtrip {
+        use super::*;
+
+        fn ip() -> Ipv4Addr {
+            Ipv4Addr::new(192, 168, 0, 1)
+        }
+
+        fn ips() -> Vec<Ipv4Addr> {
+            vec![ip(), Ipv4Addr::new(10, 0, 0, 1)]
+        }
+
+        fn name() -> Name {
+            Name::from_str("example.org.").unwrap()
+        }
+
+        roundtrip_test!(pad, DhcpOption::Pad);
+        roundtrip_test!(subnet_mask, DhcpOption::SubnetMask(ip()));
+        roundtrip_test!(time_offset, DhcpOption::TimeOffset(-3600));
+        roundtrip_test!(router, DhcpOption::Router(ips()));
+        roundtrip_test!(time_server, DhcpOption::TimeServer(ips()));
+        roundtrip_test!(name_server, DhcpOption::NameServer(ips()));
+        roundtrip_test!(domain_name_server, DhcpOption::DomainNameServer(ips()));
+        roundtrip_test!(log_server, DhcpOption::LogServer(ips()));
+        roundtrip_test!(quote_server, DhcpOption::QuoteServer(ips()));
+        roundtrip_test!(lpr_server, DhcpOption::LprServer(ips()));
+        roundtrip_test!(impress_server, DhcpOption::ImpressServer(ips()));
+        roundtrip_test!(
+            resource_location_server,
+            DhcpOption::ResourceLocationServer(ips())
+        );
+        roundtrip_test!(hostname, DhcpOption::Hostname("example.org".to_owned()));
+        roundtrip_test!(boot_file_size, DhcpOption::BootFileSize(1500));
+        roundtrip_test!(
+            merit_dump_file,
+            DhcpOption::MeritDumpFile("example.org".to_owned())
+        );
+        roundtrip_test!(
+            domain_name,
+            DhcpOption::DomainName("example.org".to_owned())
+        );
+        roundtrip_test!(swap_server, DhcpOption::SwapServer(ip()));
+        roundtrip_test!(root_path, DhcpOption::RootPath("example.org".to_owned()));
+        roundtrip_test!(
+            extensions_path,
+            DhcpOption::ExtensionsPath("example.org".to_owned())
+        );
+        roundtrip_test!(ip_forwarding, DhcpOption::IpForwarding(true));
+        roundtrip_test!(non_local_src_routing, DhcpOption::NonLocalSrcRouting(true));
+        roundtrip_test!(policy_filter, DhcpOption::PolicyFilter(vec![(ip(), ip())]));
+        roundtrip_test!(max_datagram_size, DhcpOption::MaxDatagramSize(1500));
+        roundtrip_test!(default_ip_ttl, DhcpOption::DefaultIpTtl(64));
+        roundtrip_test!(
+            path_mtu_aging_timeout,
+            DhcpOption::PathMtuAgingTimeout(86_400)
+        );
+        roundtrip_test!(
+            path_mtu_plateau_table,
+            DhcpOption::PathMtuPlateauTable(vec![68, 296, 1500])
+        );
+        roundtrip_test!(interface_mtu, DhcpOption::InterfaceMtu(1500));
+        roundtrip_test!(all_subnets_local, DhcpOption::AllSubnetsLocal(true));
+        roundtrip_test!(broadcast_addr, DhcpOption::BroadcastAddr(ip()));
+        roundtrip_test!(
+            perform_mask_discovery,
+            DhcpOption::PerformMaskDiscovery(true)
+        );
+        roundtrip_test!(mask_supplier, DhcpOption::MaskSupplier(true));
+        roundtrip_test!(
+            perform_router_discovery,
+            DhcpOption::PerformRouterDiscovery(true)
+        );
+        roundtrip_test!(
+            router_solicitation_addr,
+            DhcpOption::RouterSolicitationAddr(ip())
+        );
+        roundtrip_test!(
+            static_routing_table,
+            DhcpOption::StaticRoutingTable(vec![(ip(), ip())])
+        );
+        roundtrip_test!(trailer_encapsulated, DhcpOption::TrailerEncapsulated(true));
+        roundtrip_test!(arp_cache_timeout, DhcpOption::ArpCacheTimeout(86_400));
+        roundtrip_test!(
+            ethernet_encapsulation,
+            DhcpOption::EthernetEncapsulation(true)
+        );
+        roundtrip_test!(default_tcp_ttl, DhcpOption::DefaultTcpTtl(64));
+        roundtrip_test!(
+            tcp_keepalive_interval,
+            DhcpOption::TcpKeepaliveInterval(86_400)
+        );
+        roundtrip_test!(tcp_keepalive_garbage, DhcpOption::TcpKeepaliveGarbage(true));
+        roundtrip_test!(nis_domain, DhcpOption::NisDomain("example.org".to_owned()));
+        roundtrip_test!(nis_servers, DhcpOption::NisServers(ips()));
+        roundtrip_test!(ntp_servers, DhcpOption::NtpServers(ips()));
+        roundtrip_test!(
+            vendor_extensions,
+            DhcpOption::VendorExtensions(vec![1, 2, 3, 4])
+        );
+        roundtrip_test!(net_bios_name_servers, DhcpOption::NetBiosNameServers(ips()));
+        roundtrip_test!(
+            net_bios_datagram_distribution_server,
+            DhcpOption::NetBiosDatagramDistributionServer(ips())
+        );
+        roundtrip_test!(net_bios_node_type, DhcpOption::NetBiosNodeType(NodeType::H));
+        roundtrip_test!(
+            net_bios_scope,
+            DhcpOption::NetBiosScope("example.org".to_owned())
+        );
+        roundtrip_test!(x_font_server, DhcpOption::XFontServer(ips()));
+        roundtrip_test!(x_display_manager, DhcpOption::XDisplayManager(ips()));
+        roundtrip_test!(requested_ip_address, DhcpOption::RequestedIpAddress(ip()));
+        roundtrip_test!(address_lease_time, DhcpOption::AddressLeaseTime(86_400));
+        roundtrip_test!(option_overload, DhcpOption::OptionOverload(64));
+        roundtrip_test!(message_type, DhcpOption::MessageType(MessageType::Ack));
+        roundtrip_test!(server_identifier, DhcpOption::ServerIdentifier(ip()));
+        roundtrip_test!(
+            parameter_request_list,
+            DhcpOption::ParameterRequestList(vec![OptionCode::SubnetMask, OptionCode::Router])
+        );
+        roundtrip_test!(message, DhcpOption::Message("example.org".to_owned()));
+        roundtrip_test!(max_message_size, DhcpOption::MaxMessageSize(1500));
+        roundtrip_test!(renewal, DhcpOption::Renewal(86_400));
+        roundtrip_test!(rebinding, DhcpOption::Rebinding(86_400));
+        roundtrip_test!(
+            class_identifier,
+            DhcpOption::ClassIdentifier(vec![1, 2, 3, 4])
+        );
+        roundtrip_test!(
+            client_identifier,
+            DhcpOption::ClientIdentifier(vec![1, 2, 3, 4])
+        );
+        roundtrip_test!(
+            nwip_domain_name,
+            DhcpOption::NwipDomainName("example.org".to_owned())
+        );
+        roundtrip_test!(
+            nwip_information,
+            DhcpOption::NwipInformation(vec![1, 2, 3, 4])
+        );
+        roundtrip_test!(
+            nisp_service_domain,
+            DhcpOption::NispServiceDomain("example.org".to_owned())
+        );
+        roundtrip_test!(nisp_servers, DhcpOption::NispServers(ips()));
+        roundtrip_test!(
+            tftp_server_name,
+            DhcpOption::TFTPServerName(vec![1, 2, 3, 4])
+        );
+        roundtrip_test!(bootfile_name, DhcpOption::BootfileName(vec![1, 2, 3, 4]));
+        roundtrip_test!(mobile_ip_home_agent, DhcpOption::MobileIpHomeAgent(ips()));
+        roundtrip_test!(smtp_server, DhcpOption::SmtpServer(ips()));
+        roundtrip_test!(pop3_server, DhcpOption::Pop3Server(ips()));
+        roundtrip_test!(nntp_server, DhcpOption::NntpServer(ips()));
+        roundtrip_test!(www_server, DhcpOption::WwwServer(ips()));
+        roundtrip_test!(
+            default_finger_server,
+            DhcpOption::DefaultFingerServer(ips())
+        );
+        roundtrip_test!(irc_server, DhcpOption::IrcServer(ips()));
+        roundtrip_test!(street_talk_server, DhcpOption::StreetTalkServer(ips()));
+        roundtrip_test!(
+            street_talk_directory_assistance,
+            DhcpOption::StreetTalkDirectoryAssistance(ips())
+        );
+        roundtrip_test!(user_class, DhcpOption::UserClass(vec![1, 2, 3, 4]));
+        roundtrip_test!(rapid_commit, DhcpOption::RapidCommit);
+        roundtrip_test!(
+            client_fqdn,
+            DhcpOption::ClientFQDN(fqdn::ClientFQDN::new(
+                fqdn::FqdnFlags::default().set_e(true),
+                name()
+            ))
+        );
+        roundtrip_test!(
+            client_fqdn_ascii,
+            DhcpOption::ClientFQDN(fqdn::ClientFQDN::new(
+                fqdn::FqdnFlags::default().set_s(true),
+                name()
+            ))
+        );
+        roundtrip_test!(
+            relay_agent_information,
+            DhcpOption::RelayAgentInformation({
+                let mut info = relay::RelayAgentInformation::default();
+                info.insert(relay::RelayInfo::AgentCircuitId(vec![1, 2, 3]));
+                info.insert(relay::RelayInfo::LinkSelection(ip()));
+                info
+            })
+        );
+        roundtrip_test!(
+            bcms_controller_names,
+            DhcpOption::BcmsControllerNames(vec![name()])
+        );
+        roundtrip_test!(
+            bcms_controller_addrs,
+            DhcpOption::BcmsControllerAddrs(ips())
+        );
+        roundtrip_test!(
+            client_last_transaction_time,
+            DhcpOption::ClientLastTransactionTime(86_400)
+        );
+        roundtrip_test!(associated_ip, DhcpOption::AssociatedIp(ips()));
+        roundtrip_test!(
+            client_system_architecture,
+            DhcpOption::ClientSystemArchitecture(Architecture::X86_64)
+        );
+        roundtrip_test!(
+            client_network_interface,
+            DhcpOption::ClientNetworkInterface(1, 3, 16)
+        );
+        roundtrip_test!(
+            client_machine_identifier,
+            DhcpOption::ClientMachineIdentifier(vec![1, 2, 3, 4])
+        );
+        roundtrip_test!(
+            pcode,
+            DhcpOption::PCode("EST5EDT4,M3.2.0/02:00,M11.1.0/02:00".to_owned())
+        );
+        roundtrip_test!(tcode, DhcpOption::TCode("Europe/Zurich".to_owned()));
+        roundtrip_test!(ipv6_only_preferred, DhcpOption::Ipv6OnlyPreferred(1800));
+        roundtrip_test!(
+            captive_portal,
+            DhcpOption::CaptivePortal(url::Url::parse("https://example.org/portal").unwrap())
+        );
+        roundtrip_test!(auto_config, DhcpOption::AutoConfig(1));
+        roundtrip_test!(nds_servers, DhcpOption::NdsServers(ips()));
+        roundtrip_test!(nds_tree_name, DhcpOption::NdsTreeName("CORP".to_owned()));
+        roundtrip_test!(
+            nds_context,
+            DhcpOption::NdsContext("OU=Staff.O=Corp".to_owned())
+        );
+        roundtrip_test!(
+            slp_directory_agent,
+            DhcpOption::SlpDirectoryAgent(true, ips())
+        );
+        roundtrip_test!(
+            slp_service_scope,
+            DhcpOption::SlpServiceScope(false, "default".to_owned())
+        );
+        roundtrip_test!(
+            mud_url,
+            DhcpOption::MudUrl("https://example.org/mud/device.json".to_owned())
+        );
+        roundtrip_test!(
+            wpad_url,
+            DhcpOption::WpadUrl("http://wpad.example.org/wpad.dat".to_owned())
+        );
+        roundtrip_test!(subnet_selection, DhcpOption::SubnetSelection(ip()));
+        roundtrip_test!(
+            domain_search,
+            DhcpOption::DomainSearch(vec![name(), Name::from_str("example.net.").unwrap()])
+        );
+        roundtrip_test!(
+            classless_static_route,
+            DhcpOption::ClasslessStaticRoute(vec![("10.0.0.0/8".parse().unwrap(), ip())])
+        );
+        // enterprise number 3561 (DSL Forum) with one class/sub-option
+        roundtrip_test!(
+            vi_vendor_class,
+            DhcpOption::ViVendorClass(vec![0, 0, 0x0d, 0xe9, 4, 3, b'a', b'b', b'c'])
+        );
+        roundtrip_test!(
+            vi_vendor_specific_info,
+            DhcpOption::ViVendorSpecificInfo(vec![0, 0, 0x0d, 0xe9, 5, 1, 3, b'a', b'b', b'c'])
+        );
+        roundtrip_test!(tftp_server_address, DhcpOption::TFTPServerAddress(ip()));
+        roundtrip_test!(
+            bulk_lease_query_status_code,
+            DhcpOption::BulkLeaseQueryStatusCode(bulk_query::Code::NotAllowed, "denied".to_owned())
+        );
+        roundtrip_test!(
+            bulk_lease_query_base_time,
+            DhcpOption::BulkLeaseQueryBaseTime(86_400)
+        );
+        roundtrip_test!(
+            bulk_leas_query_start_time_of_state,
+            DhcpOption::BulkLeasQueryStartTimeOfState(86_400)
+        );
+        roundtrip_test!(
+            bulk_lease_query_query_start_time,
+            DhcpOption::BulkLeaseQueryQueryStartTime(86_400)
+        );
+        roundtrip_test!(
+            bulk_lease_query_query_end_time,
+            DhcpOption::BulkLeaseQueryQueryEndTime(86_400)
+        );
+        roundtrip_test!(
+            bulk_lease_query_dhcp_state,
+            DhcpOption::BulkLeaseQueryDhcpState(bulk_query::QueryState::Active)
+        );
+        roundtrip_test!(
+            bulk_lease_query_data_source,
+            DhcpOption::BulkLeaseQueryDataSource(bulk_query::DataSourceFlags::new(1))
+        );
+        roundtrip_test!(end, DhcpOption::End);
+        roundtrip_test!(
+            unknown,
+            DhcpOption::Unknown(UnknownOption::new(OptionCode::Unknown(224), vec![1, 2, 3]))
+        );
+    }
 }
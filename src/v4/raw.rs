@@ -0,0 +1,331 @@
+//! Borrowed, zero-copy access to DHCPv4 messages & options
+use std::net::Ipv4Addr;
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    error::{DecodeError, DecodeResult},
+    sub_option::TlvIter,
+    v4::{
+        options::decode_inner, DhcpOption, Flags, HType, Message, MessageType, Opcode, OptionCode,
+    },
+};
+
+/// length of the fixed DHCPv4 header, including the magic cookie
+pub(crate) const HEADER_LEN: usize = 240;
+
+/// A single option borrowed from the original buffer.
+///
+/// Unlike [`DhcpOption`], the payload is not copied or parsed until
+/// [`RawOption::to_option`] is called.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RawOption<'a> {
+    code: OptionCode,
+    data: &'a [u8],
+}
+
+impl<'a> RawOption<'a> {
+    /// create a new `RawOption` from its code and payload
+    pub fn new(code: OptionCode, data: &'a [u8]) -> Self {
+        Self { code, data }
+    }
+
+    /// the option's code
+    pub fn code(&self) -> OptionCode {
+        self.code
+    }
+
+    /// the option's payload, not including code & length
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// parse the payload into an owned [`DhcpOption`]
+    pub fn to_option(&self) -> DecodeResult<DhcpOption> {
+        decode_inner(self.code, self.data.len(), &mut Decoder::new(self.data))
+    }
+}
+
+/// Iterator over the options in a buffer, yielding [`RawOption`]s that
+/// reference the original bytes.
+///
+/// `Pad` is skipped and iteration stops at `End`. Long options split
+/// according to [RFC 3396](https://www.rfc-editor.org/rfc/rfc3396) can't be
+/// joined without copying, so each occurrence is yielded separately.
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use dhcproto::v4::{OptionCode, RawOptions};
+///
+/// let buf = [53, 1, 1, 0, 0, 54, 4, 192, 168, 0, 1, 255];
+/// let codes = RawOptions::new(&buf)
+///     .map(|opt| opt.map(|opt| opt.code()))
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(codes, [OptionCode::MessageType, OptionCode::ServerIdentifier]);
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RawOptions<'a>(TlvIter<'a>);
+
+impl<'a> RawOptions<'a> {
+    /// iterate over an options buffer (the bytes following the magic cookie)
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self(TlvIter::new(buf))
+    }
+
+    /// iterate over the options of an encoded DHCPv4 message
+    pub fn from_message(msg: &'a [u8]) -> DecodeResult<Self> {
+        msg.get(HEADER_LEN..)
+            .map(Self::new)
+            .ok_or(DecodeError::NotEnoughBytes)
+    }
+}
+
+impl<'a> Iterator for RawOptions<'a> {
+    type Item = DecodeResult<RawOption<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next()
+            .map(|opt| opt.map(|(code, data)| RawOption::new(code.into(), data)))
+    }
+}
+
+/// Iterator that decodes each option when `next` is called, see [`RawOptions`]
+/// for how the buffer is walked.
+#[derive(Debug, Clone)]
+pub struct LazyOptions<'a>(RawOptions<'a>);
+
+impl<'a> LazyOptions<'a> {
+    /// iterate over an options buffer (the bytes following the magic cookie)
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self(RawOptions::new(buf))
+    }
+}
+
+impl<'a> Iterator for LazyOptions<'a> {
+    type Item = DecodeResult<DhcpOption>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|opt| opt?.to_option())
+    }
+}
+
+/// A DHCPv4 message borrowed from an encoded buffer. Header fields are read
+/// from the buffer when accessed and options are only decoded by
+/// [`LazyMessage::get`] or [`LazyMessage::opts`].
+///
+/// Useful when only a few options are needed, i.e. a relay agent looking at
+/// option 82.
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use dhcproto::v4::{DhcpOption, Encodable, LazyMessage, Message, MessageType};
+///
+/// let mut msg = Message::default();
+/// msg.opts_mut().insert(DhcpOption::MessageType(MessageType::Discover));
+/// let buf = msg.to_vec()?;
+///
+/// let lazy = LazyMessage::new(&buf)?;
+/// assert_eq!(lazy.xid(), msg.xid());
+/// assert_eq!(lazy.msg_type()?, Some(MessageType::Discover));
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LazyMessage<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> LazyMessage<'a> {
+    /// wrap an encoded message. Returns an error if `buf` is shorter than the
+    /// fixed header.
+    pub fn new(buf: &'a [u8]) -> DecodeResult<Self> {
+        if buf.len() < HEADER_LEN {
+            return Err(DecodeError::NotEnoughBytes);
+        }
+        Ok(Self { buf })
+    }
+
+    fn read<const N: usize>(&self, offset: usize) -> [u8; N] {
+        // length checked in `new`
+        let mut out = [0; N];
+        out.copy_from_slice(&self.buf[offset..offset + N]);
+        out
+    }
+
+    /// Get the message's opcode.
+    pub fn opcode(&self) -> Opcode {
+        self.buf[0].into()
+    }
+
+    /// Get the message's hardware type.
+    pub fn htype(&self) -> HType {
+        self.buf[1].into()
+    }
+
+    /// Get the message's hardware len (len of chaddr).
+    pub fn hlen(&self) -> u8 {
+        self.buf[2]
+    }
+
+    /// Get the message's hops.
+    pub fn hops(&self) -> u8 {
+        self.buf[3]
+    }
+
+    /// Get the message's xid.
+    pub fn xid(&self) -> u32 {
+        u32::from_be_bytes(self.read(4))
+    }
+
+    /// Get the message's secs.
+    pub fn secs(&self) -> u16 {
+        u16::from_be_bytes(self.read(8))
+    }
+
+    /// Get the message's flags.
+    pub fn flags(&self) -> Flags {
+        u16::from_be_bytes(self.read(10)).into()
+    }
+
+    /// Get the message's ciaddr.
+    pub fn ciaddr(&self) -> Ipv4Addr {
+        self.read::<4>(12).into()
+    }
+
+    /// Get the message's yiaddr.
+    pub fn yiaddr(&self) -> Ipv4Addr {
+        self.read::<4>(16).into()
+    }
+
+    /// Get the message's siaddr.
+    pub fn siaddr(&self) -> Ipv4Addr {
+        self.read::<4>(20).into()
+    }
+
+    /// Get the message's giaddr.
+    pub fn giaddr(&self) -> Ipv4Addr {
+        self.read::<4>(24).into()
+    }
+
+    /// Get the message's chaddr.
+    pub fn chaddr(&self) -> &'a [u8] {
+        &self.buf[28..28 + (self.hlen() as usize).min(16)]
+    }
+
+    /// iterate over the message's options, borrowing their payloads
+    pub fn raw_opts(&self) -> RawOptions<'a> {
+        RawOptions::new(&self.buf[HEADER_LEN..])
+    }
+
+    /// iterate over the message's options, decoding each one as it is reached
+    pub fn opts(&self) -> LazyOptions<'a> {
+        LazyOptions::new(&self.buf[HEADER_LEN..])
+    }
+
+    /// find & decode the option with `code`. Split options
+    /// ([RFC 3396](https://www.rfc-editor.org/rfc/rfc3396)) are joined before decoding.
+    pub fn get(&self, code: OptionCode) -> DecodeResult<Option<DhcpOption>> {
+        let mut found: Option<RawOption<'a>> = None;
+        let mut joined: Option<Vec<u8>> = None;
+        for opt in self.raw_opts() {
+            let opt = opt?;
+            if opt.code() != code {
+                continue;
+            }
+            match (&found, &mut joined) {
+                (None, _) => found = Some(opt),
+                (Some(first), None) => {
+                    joined = Some([first.data(), opt.data()].concat());
+                }
+                (Some(_), Some(buf)) => buf.extend_from_slice(opt.data()),
+            }
+        }
+        match (found, joined) {
+            (_, Some(buf)) => RawOption::new(code, &buf).to_option().map(Some),
+            (Some(opt), None) => opt.to_option().map(Some),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// return message type
+    pub fn msg_type(&self) -> DecodeResult<Option<MessageType>> {
+        Ok(match self.get(OptionCode::MessageType)? {
+            Some(DhcpOption::MessageType(mtype)) => Some(mtype),
+            _ => None,
+        })
+    }
+
+    /// fully decode into an owned [`Message`]
+    pub fn to_message(&self) -> DecodeResult<Message> {
+        Message::decode(&mut Decoder::new(self.buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    #[test]
+    fn test_raw_options() -> DecodeResult<()> {
+        let buf = [53, 1, 2, 0, 0, 0, 54, 4, 192, 168, 0, 1, 255, 1, 1, 1];
+        let opts = RawOptions::new(&buf).collect::<DecodeResult<Vec<_>>>()?;
+        assert_eq!(opts.len(), 2);
+        assert_eq!(opts[0].code(), OptionCode::MessageType);
+        assert_eq!(opts[0].data(), &[2]);
+        // payload borrows from the original buffer
+        assert!(std::ptr::eq(opts[1].data().as_ptr(), &buf[8]));
+        assert_eq!(
+            opts[1].to_option()?,
+            DhcpOption::ServerIdentifier(Ipv4Addr::new(192, 168, 0, 1))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lazy_message() -> DecodeResult<()> {
+        let mut msg = Message::default();
+        msg.set_chaddr(&[1, 2, 3, 4, 5, 6])
+            .set_yiaddr([192, 168, 0, 2])
+            .opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Offer));
+        msg.opts_mut()
+            .insert(DhcpOption::DomainName("a".repeat(300)));
+        let buf = crate::encoder::Encodable::to_vec(&msg).unwrap();
+
+        let lazy = LazyMessage::new(&buf)?;
+        assert_eq!(lazy.opcode(), msg.opcode());
+        assert_eq!(lazy.xid(), msg.xid());
+        assert_eq!(lazy.chaddr(), msg.chaddr());
+        assert_eq!(lazy.yiaddr(), msg.yiaddr());
+        assert_eq!(lazy.msg_type()?, Some(MessageType::Offer));
+        // split across two options on the wire
+        assert_eq!(
+            lazy.get(OptionCode::DomainName)?,
+            msg.opts().get(OptionCode::DomainName).cloned()
+        );
+        assert_eq!(lazy.get(OptionCode::Router)?, None);
+        assert_eq!(lazy.opts().count(), 3);
+        assert_eq!(lazy.to_message()?, msg);
+
+        assert!(LazyMessage::new(&buf[..100]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw_options_truncated() {
+        let buf = [53, 1, 2, 54, 4, 192, 168];
+        let mut opts = RawOptions::new(&buf);
+        assert!(opts.next().unwrap().is_ok());
+        assert!(matches!(
+            opts.next().unwrap(),
+            Err(DecodeError::TruncatedOption {
+                code: 54,
+                declared_len: 4,
+                available: 2
+            })
+        ));
+        assert!(opts.next().is_none());
+    }
+}
@@ -1,7 +1,7 @@
 //!
 use std::{collections::HashMap, fmt, net::Ipv4Addr};
 
-use crate::{Decodable, Encodable};
+use crate::{error::EncodeError, v4::DhcpOption, Decodable, Encodable};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -74,6 +74,74 @@ impl RelayAgentInformation {
     }
 }
 
+/// Builds option 82 from its most common sub-options, checking each one
+/// fits in a sub-option length
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use dhcproto::v4::{self, relay::RelayAgentInfoBuilder};
+///
+/// let opt = RelayAgentInfoBuilder::new()
+///     .circuit_id(b"eth0:100")?
+///     .remote_id(&[0x00, 0x1e, 0x58, 0x4f, 0x23, 0x41])?
+///     .build();
+/// let mut msg = v4::Message::default();
+/// msg.opts_mut().insert(opt);
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RelayAgentInfoBuilder {
+    info: RelayAgentInformation,
+}
+
+impl RelayAgentInfoBuilder {
+    /// create a builder with no sub-options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// set the agent circuit id (sub-option 1), i.e. the port the request
+    /// was received on
+    pub fn circuit_id(mut self, bytes: &[u8]) -> Result<Self, EncodeError> {
+        check_len(RelayCode::AgentCircuitId, bytes.len())?;
+        self.info.insert(RelayInfo::AgentCircuitId(bytes.to_vec()));
+        Ok(self)
+    }
+
+    /// set the agent remote id (sub-option 2), i.e. the agent's MAC address
+    /// or a NAS identifier
+    pub fn remote_id(mut self, bytes: &[u8]) -> Result<Self, EncodeError> {
+        check_len(RelayCode::AgentRemoteId, bytes.len())?;
+        self.info.insert(RelayInfo::AgentRemoteId(bytes.to_vec()));
+        Ok(self)
+    }
+
+    /// set the agent remote id (sub-option 2) to an enterprise number
+    /// followed by `value`, the same layout as the DHCPv6 remote-id
+    /// <https://datatracker.ietf.org/doc/html/rfc4649#section-3>
+    pub fn agent_remote_id(self, enterprise: u32, value: &[u8]) -> Result<Self, EncodeError> {
+        let mut bytes = Vec::with_capacity(4 + value.len());
+        bytes.extend_from_slice(&enterprise.to_be_bytes());
+        bytes.extend_from_slice(value);
+        self.remote_id(&bytes)
+    }
+
+    /// return the [`DhcpOption::RelayAgentInformation`] option
+    pub fn build(self) -> DhcpOption {
+        DhcpOption::RelayAgentInformation(self.info)
+    }
+}
+
+fn check_len(code: RelayCode, len: usize) -> Result<(), EncodeError> {
+    if len > u8::MAX as usize {
+        return Err(EncodeError::SubOptionTooLong {
+            code: code.into(),
+            len,
+        });
+    }
+    Ok(())
+}
+
 impl Decodable for RelayAgentInformation {
     fn decode(d: &mut crate::Decoder<'_>) -> super::DecodeResult<Self> {
         let mut opts = HashMap::new();
@@ -134,7 +202,7 @@ impl Decodable for RelayInfo {
             RelayCode::AgentRemoteId => {
                 let len = d.read_u8()? as usize;
                 let data = d.read_slice(len)?.to_vec();
-                AgentCircuitId(data)
+                AgentRemoteId(data)
             }
             RelayCode::DocsisDeviceClass => {
                 let _ = d.read_u8()?;
@@ -199,7 +267,7 @@ impl Encodable for RelayInfo {
             }
             R::LinkSelection(addr) | R::ServerIdentifierOverride(addr) => {
                 e.write_u8(4)?;
-                e.write_u32((*addr).into())?
+                e.write_ipv4(*addr)?
             }
             R::RelayAgentFlags(flags) => {
                 e.write_u8(1)?;
@@ -432,4 +500,45 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_builder() -> Result<()> {
+        // option 82 from a capture of a switch relaying a DISCOVER:
+        // circuit-id vlan 100, module 1, port 5 & remote-id of the switch MAC
+        let captured = [
+            82, 18, 1, 6, 0, 4, 0, 100, 1, 5, 2, 8, 0, 6, 0, 0x1e, 0x58, 0x4f, 0x23, 0x41,
+        ];
+        let opt = RelayAgentInfoBuilder::new()
+            .circuit_id(&[0, 4, 0, 100, 1, 5])?
+            .remote_id(&[0, 6, 0, 0x1e, 0x58, 0x4f, 0x23, 0x41])?
+            .build();
+        assert_eq!(
+            DhcpOption::decode(&mut crate::Decoder::new(&captured))?,
+            opt
+        );
+        // sub-option order isn't preserved, but the length is
+        assert_eq!(opt.to_vec()?.len(), captured.len());
+
+        let opt = RelayAgentInfoBuilder::new()
+            .agent_remote_id(9, b"nas1")?
+            .build();
+        match opt {
+            DhcpOption::RelayAgentInformation(info) => assert_eq!(
+                info.get(RelayCode::AgentRemoteId),
+                Some(&RelayInfo::AgentRemoteId(vec![
+                    0, 0, 0, 9, b'n', b'a', b's', b'1'
+                ]))
+            ),
+            _ => unreachable!(),
+        }
+
+        assert!(matches!(
+            RelayAgentInfoBuilder::new().circuit_id(&[0; 256]),
+            Err(EncodeError::SubOptionTooLong { code: 1, len: 256 })
+        ));
+        assert!(RelayAgentInfoBuilder::new()
+            .agent_remote_id(9, &[0; 252])
+            .is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,256 @@
+//! Helpers for working with DHCPv4 option values & packets
+use std::net::Ipv4Addr;
+
+use crate::{
+    encoder::Encodable,
+    error::{EncodeError, EncodeResult, NetmaskError},
+    v4::{Message, CLIENT_PORT, SERVER_PORT},
+    Name,
+};
+
+/// Convert a subnet mask (option 1) to a prefix length, as used by
+/// classless static routes (option 121). Returns an error if the mask
+/// isn't contiguous.
+///
+/// ```rust
+/// use std::net::Ipv4Addr;
+/// use dhcproto::v4::util::netmask_to_prefix_len;
+///
+/// assert_eq!(netmask_to_prefix_len(Ipv4Addr::new(255, 255, 255, 0)), Ok(24));
+/// assert!(netmask_to_prefix_len(Ipv4Addr::new(255, 0, 255, 0)).is_err());
+/// ```
+pub fn netmask_to_prefix_len(mask: Ipv4Addr) -> Result<u8, NetmaskError> {
+    let bits = u32::from(mask);
+    let len = bits.leading_ones();
+    // everything after the leading ones must be zero
+    if bits.checked_shl(len).unwrap_or(0) != 0 {
+        return Err(NetmaskError::NonContiguous(mask));
+    }
+    Ok(len as u8)
+}
+
+/// Convert a prefix length to a subnet mask. Returns an error if `len` is
+/// greater than 32.
+///
+/// ```rust
+/// use std::net::Ipv4Addr;
+/// use dhcproto::v4::util::prefix_len_to_netmask;
+///
+/// assert_eq!(prefix_len_to_netmask(20), Ok(Ipv4Addr::new(255, 255, 240, 0)));
+/// assert!(prefix_len_to_netmask(33).is_err());
+/// ```
+pub fn prefix_len_to_netmask(len: u8) -> Result<Ipv4Addr, NetmaskError> {
+    if len > 32 {
+        return Err(NetmaskError::InvalidPrefixLen(len));
+    }
+    Ok(u32::MAX.checked_shl(32 - len as u32).unwrap_or(0).into())
+}
+
+// IPv4 + UDP headers
+const IP_UDP_HEADER_LEN: usize = 28;
+
+/// Encode `msg` in an Ethernet frame for a client broadcast, from
+/// `0.0.0.0:68` to `255.255.255.255:67` and `ff:ff:ff:ff:ff:ff`. Before it
+/// has an address a client can send this on a raw (`AF_PACKET`) socket,
+/// opening the socket is left to the caller.
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use dhcproto::v4::{util::broadcast_frame, Message};
+///
+/// let mac = [0, 1, 2, 3, 4, 5];
+/// let frame = broadcast_frame(mac, &Message::default())?;
+/// assert_eq!(&frame[..6], &[0xff; 6]);
+/// assert_eq!(&frame[6..12], &mac);
+/// # Ok(()) }
+/// ```
+pub fn broadcast_frame(src_mac: [u8; 6], msg: &Message) -> EncodeResult<Vec<u8>> {
+    let payload = msg.to_vec()?;
+    let limit = u16::MAX as usize - IP_UDP_HEADER_LEN;
+    if payload.len() > limit {
+        return Err(EncodeError::MessageTooLarge { limit });
+    }
+    let src = Ipv4Addr::UNSPECIFIED.octets();
+    let dst = Ipv4Addr::BROADCAST.octets();
+    let udp_len = (8 + payload.len()) as u16;
+
+    let mut frame = Vec::with_capacity(14 + IP_UDP_HEADER_LEN + payload.len());
+    // ethernet
+    frame.extend([0xff; 6]);
+    frame.extend(src_mac);
+    frame.extend(0x0800u16.to_be_bytes());
+    // ipv4, no options
+    let mut ip = [0; 20];
+    ip[0] = 0x45;
+    ip[2..4].copy_from_slice(&(20 + udp_len).to_be_bytes());
+    ip[8] = 64; // ttl
+    ip[9] = 17; // udp
+    ip[12..16].copy_from_slice(&src);
+    ip[16..20].copy_from_slice(&dst);
+    let ip_sum = checksum(&[&ip]);
+    ip[10..12].copy_from_slice(&ip_sum.to_be_bytes());
+    frame.extend(ip);
+    // udp, checksum includes the pseudo header
+    let mut udp = [0; 8];
+    udp[0..2].copy_from_slice(&CLIENT_PORT.to_be_bytes());
+    udp[2..4].copy_from_slice(&SERVER_PORT.to_be_bytes());
+    udp[4..6].copy_from_slice(&udp_len.to_be_bytes());
+    let pseudo = [&src[..], &dst[..], &[0, 17], &udp_len.to_be_bytes()].concat();
+    let udp_sum = match checksum(&[&pseudo, &udp, &payload]) {
+        // 0 means no checksum, send all ones instead
+        0 => 0xffff,
+        sum => sum,
+    };
+    udp[6..8].copy_from_slice(&udp_sum.to_be_bytes());
+    frame.extend(udp);
+    frame.extend(payload);
+    Ok(frame)
+}
+
+/// Format `bytes` like Wireshark's "Copy as Hex Dump": 16 bytes per line
+/// with the offset on the left & printable ASCII on the right. The output
+/// can be imported with Wireshark's "Import from Hex Dump" or `text2pcap`.
+///
+/// ```rust
+/// use dhcproto::v4::util::hex_dump;
+///
+/// assert_eq!(
+///     hex_dump(b"DHCP\x01"),
+///     "0000   44 48 43 50 01                                    DHCP.\n"
+/// );
+/// ```
+pub fn hex_dump(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() / 16 + 1) * 74);
+    for (i, line) in bytes.chunks(16).enumerate() {
+        let hex = line
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ascii = line
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        out.push_str(&format!("{:04x}   {hex:<47}   {ascii}\n", i * 16));
+    }
+    out
+}
+
+/// Format a domain search list (option 119) as a `search` line for
+/// `/etc/resolv.conf`. Names are written in ASCII (punycode) without the
+/// trailing dot. Returns an empty string if there are no names.
+///
+/// ```rust
+/// use std::str::FromStr;
+/// use dhcproto::{v4::util::resolv_conf_search, Name};
+///
+/// let names = [
+///     Name::from_str("example.com.").unwrap(),
+///     Name::from_str("corp.example.com").unwrap(),
+/// ];
+/// assert_eq!(
+///     resolv_conf_search(&names),
+///     "search example.com corp.example.com"
+/// );
+/// ```
+pub fn resolv_conf_search(names: &[Name]) -> String {
+    let domains = names
+        .iter()
+        .map(|name| name.to_ascii())
+        .map(|name| name.trim_end_matches('.').to_owned())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    if domains.is_empty() {
+        return String::new();
+    }
+    format!("search {}", domains.join(" "))
+}
+
+// internet checksum over the concatenation of `parts`, all but the last
+// must be of even length
+fn checksum(parts: &[&[u8]]) -> u16 {
+    let mut sum = parts
+        .iter()
+        .flat_map(|part| part.chunks(2))
+        .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]) as u32)
+        .sum::<u32>();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_netmask_prefix_len() {
+        for len in 0..=32 {
+            let mask = prefix_len_to_netmask(len).unwrap();
+            assert_eq!(netmask_to_prefix_len(mask), Ok(len));
+        }
+        assert_eq!(prefix_len_to_netmask(0), Ok(Ipv4Addr::UNSPECIFIED));
+        assert_eq!(prefix_len_to_netmask(32), Ok(Ipv4Addr::BROADCAST));
+        let mask = Ipv4Addr::new(255, 255, 0, 255);
+        assert_eq!(
+            netmask_to_prefix_len(mask),
+            Err(NetmaskError::NonContiguous(mask))
+        );
+        assert_eq!(
+            prefix_len_to_netmask(33),
+            Err(NetmaskError::InvalidPrefixLen(33))
+        );
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        assert_eq!(hex_dump(&[]), "");
+        let bytes = (0..18).map(|i| i + 0x40).collect::<Vec<u8>>();
+        assert_eq!(
+            hex_dump(&bytes),
+            "0000   40 41 42 43 44 45 46 47 48 49 4a 4b 4c 4d 4e 4f   @ABCDEFGHIJKLMNO\n\
+             0010   50 51                                             PQ\n"
+        );
+    }
+
+    #[test]
+    fn test_resolv_conf_search() {
+        use std::str::FromStr;
+        assert_eq!(resolv_conf_search(&[]), "");
+        assert_eq!(resolv_conf_search(&[Name::root()]), "");
+        let names = [
+            Name::from_str("example.org.").unwrap(),
+            Name::from_str("lab.example.org").unwrap(),
+        ];
+        assert_eq!(
+            resolv_conf_search(&names),
+            "search example.org lab.example.org"
+        );
+    }
+
+    #[test]
+    fn test_broadcast_frame() -> Result<(), Box<dyn std::error::Error>> {
+        let msg = Message::default();
+        let payload = msg.to_vec()?;
+        let frame = broadcast_frame([0, 1, 2, 3, 4, 5], &msg)?;
+        assert_eq!(frame.len(), 14 + 28 + payload.len());
+        assert_eq!(&frame[12..14], &[0x08, 0x00]);
+        let ip = &frame[14..34];
+        // valid header sums to 0
+        assert_eq!(checksum(&[ip]), 0);
+        assert_eq!(&ip[16..20], &[255; 4]);
+        let udp = &frame[34..];
+        assert_eq!(&udp[..4], &[0, 68, 0, 67]);
+        let pseudo = [0, 0, 0, 0, 255, 255, 255, 255, 0, 17, udp[4], udp[5]];
+        assert_eq!(checksum(&[&pseudo, udp]), 0);
+        assert_eq!(&udp[8..], &payload[..]);
+        Ok(())
+    }
+}
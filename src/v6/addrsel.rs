@@ -0,0 +1,141 @@
+//! Address selection policy (RFC 6724 policy table) options
+//! <https://datatracker.ietf.org/doc/html/rfc7078>
+use ipnet::Ipv6Net;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    encoder::{Encodable, Encoder},
+    error::{DecodeResult, EncodeResult},
+    sub_option::{SubOptionCodec, U16Tlv},
+    v6::{
+        s46::{read_prefix6, write_prefix6},
+        OptionCode,
+    },
+};
+
+const AUTOMATIC_FLAG: u8 = 0x02;
+const PRIVACY_FLAG: u8 = 0x01;
+
+/// 84 - address selection policy, the `A` & `P` flags followed by
+/// [`PolicyEntry`]s sent as `OPTION_ADDRSEL_TABLE` (85) options. Other
+/// options nested in it are ignored when decoding.
+/// <https://datatracker.ietf.org/doc/html/rfc7078#section-4>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AddressSelectionPolicy {
+    /// `A` flag, the client may add rows to the policy table automatically
+    pub automatic: bool,
+    /// `P` flag, prefer temporary (privacy) addresses
+    pub privacy: bool,
+    pub entries: Vec<PolicyEntry>,
+}
+
+/// a row of the policy table
+/// <https://datatracker.ietf.org/doc/html/rfc6724#section-2.1>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PolicyEntry {
+    pub prefix: Ipv6Net,
+    pub precedence: u8,
+    pub label: u8,
+}
+
+impl Decodable for AddressSelectionPolicy {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        let flags = decoder.read_u8()?;
+        let mut entries = Vec::new();
+        for (code, data) in U16Tlv::decode(decoder)? {
+            if OptionCode::from(code) == OptionCode::AddrselTable {
+                entries.push(PolicyEntry::decode(&mut Decoder::new(&data))?);
+            }
+        }
+        Ok(AddressSelectionPolicy {
+            automatic: flags & AUTOMATIC_FLAG != 0,
+            privacy: flags & PRIVACY_FLAG != 0,
+            entries,
+        })
+    }
+}
+
+impl Encodable for AddressSelectionPolicy {
+    fn encode(&self, e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
+        let mut flags = 0;
+        if self.automatic {
+            flags |= AUTOMATIC_FLAG;
+        }
+        if self.privacy {
+            flags |= PRIVACY_FLAG;
+        }
+        e.write_u8(flags)?;
+        self.entries.iter().try_for_each(|entry| {
+            e.write_v6_option_with(OptionCode::AddrselTable.into(), |e| entry.encode(e))
+        })
+    }
+}
+
+impl Decodable for PolicyEntry {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        let label = decoder.read_u8()?;
+        let precedence = decoder.read_u8()?;
+        let (len, prefix) = read_prefix6(OptionCode::AddrselTable, decoder)?;
+        Ok(PolicyEntry {
+            // read_prefix6 rejects lengths over 128
+            prefix: Ipv6Net::new(prefix, len).expect("valid prefix length"),
+            precedence,
+            label,
+        })
+    }
+}
+
+impl Encodable for PolicyEntry {
+    fn encode(&self, e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
+        e.write_u8(self.label)?;
+        e.write_u8(self.precedence)?;
+        write_prefix6(
+            OptionCode::AddrselTable,
+            self.prefix.prefix_len(),
+            self.prefix.network(),
+            e,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v6::DhcpOption;
+
+    #[test]
+    fn decode_addrsel() -> DecodeResult<()> {
+        #[rustfmt::skip]
+        let buf = [
+            0, 84, 0, 28,
+            0x03,
+            // ::1/128, precedence 50, label 0
+            0, 85, 0, 19,
+            0, 50, 128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+            // unknown
+            0, 99, 0, 0,
+        ];
+        let opt = DhcpOption::decode(&mut Decoder::new(&buf))?;
+        let policy = AddressSelectionPolicy {
+            automatic: true,
+            privacy: true,
+            entries: vec![PolicyEntry {
+                prefix: "::1/128".parse().unwrap(),
+                precedence: 50,
+                label: 0,
+            }],
+        };
+        assert_eq!(opt, DhcpOption::Addrsel(policy));
+        // the unknown option is dropped
+        assert_eq!(
+            opt.to_vec().unwrap(),
+            [&[0, 84, 0, 24], &buf[4..28]].concat()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+//! [`proptest`] strategies for DHCPv6 types, enabled with the `proptest` feature
+use std::net::Ipv6Addr;
+
+use proptest::prelude::*;
+
+use crate::{
+    v4::HType,
+    v6::{
+        duid::Duid, ClientLinklayerAddr, DhcpOption, DhcpOptions, IAAddr, IAPrefix, Message,
+        MessageType, OptionCode, RelayMessage, Status, StatusCode, UserClass, VendorClass, IANA,
+        IAPD, ORO,
+    },
+};
+
+impl Arbitrary for MessageType {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        // client/server message types, relay messages have a different header
+        (1..=11u8).prop_map(MessageType::from).boxed()
+    }
+}
+
+impl Arbitrary for OptionCode {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<u16>().prop_map(OptionCode::from).boxed()
+    }
+}
+
+impl Arbitrary for Duid {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let htype = || any::<u8>().prop_map(HType::from);
+        prop_oneof![
+            (htype(), any::<u32>(), any::<Ipv6Addr>())
+                .prop_map(|(htype, time, addr)| Duid::link_layer_time(htype, time, addr)),
+            (any::<u32>(), prop::collection::vec(any::<u8>(), 1..16))
+                .prop_map(|(enterprise, id)| Duid::enterprise(enterprise, &id)),
+            (htype(), any::<Ipv6Addr>()).prop_map(|(htype, addr)| Duid::link_layer(htype, addr)),
+            any::<[u8; 16]>().prop_map(|uuid| Duid::uuid(&uuid)),
+        ]
+        .boxed()
+    }
+}
+
+fn status_code() -> impl Strategy<Value = DhcpOption> {
+    (0..=22u16, "[a-zA-Z ]{0,32}").prop_map(|(status, msg)| {
+        DhcpOption::StatusCode(StatusCode {
+            status: Status::from(status),
+            msg,
+        })
+    })
+}
+
+// (t1, t2) where t1 <= t2
+fn timers() -> impl Strategy<Value = (u32, u32)> {
+    (any::<u32>(), any::<u32>()).prop_map(|(a, b)| (a.min(b), a.max(b)))
+}
+
+fn ia_addr() -> impl Strategy<Value = DhcpOption> {
+    (any::<Ipv6Addr>(), timers(), prop::option::of(status_code())).prop_map(
+        |(addr, (preferred_life, valid_life), status)| {
+            DhcpOption::IAAddr(IAAddr {
+                addr,
+                preferred_life,
+                valid_life,
+                opts: status.into_iter().collect(),
+            })
+        },
+    )
+}
+
+fn ia_prefix() -> impl Strategy<Value = DhcpOption> {
+    (any::<Ipv6Addr>(), 0..=128u8, timers()).prop_map(
+        |(prefix_ip, prefix_len, (preferred_lifetime, valid_lifetime))| {
+            DhcpOption::IAPrefix(IAPrefix {
+                preferred_lifetime,
+                valid_lifetime,
+                prefix_len,
+                prefix_ip,
+                opts: DhcpOptions::new(),
+            })
+        },
+    )
+}
+
+// IA_NA/IA_PD options, one per IAID
+fn ias() -> impl Strategy<Value = Vec<DhcpOption>> {
+    (
+        prop::collection::hash_set(any::<u32>(), 0..4),
+        prop::collection::hash_set(any::<u32>(), 0..4),
+    )
+        .prop_flat_map(|(na_ids, pd_ids)| {
+            let na = na_ids
+                .into_iter()
+                .map(|id| {
+                    (timers(), prop::collection::vec(ia_addr(), 0..3)).prop_map(
+                        move |((t1, t2), addrs)| {
+                            DhcpOption::IANA(IANA {
+                                id,
+                                t1,
+                                t2,
+                                opts: addrs.into_iter().collect(),
+                            })
+                        },
+                    )
+                })
+                .collect::<Vec<_>>();
+            let pd = pd_ids
+                .into_iter()
+                .map(|id| {
+                    (timers(), prop::collection::vec(ia_prefix(), 0..3)).prop_map(
+                        move |((t1, t2), prefixes)| {
+                            DhcpOption::IAPD(IAPD {
+                                id,
+                                t1,
+                                t2,
+                                opts: prefixes.into_iter().collect(),
+                            })
+                        },
+                    )
+                })
+                .collect::<Vec<_>>();
+            (na, pd)
+        })
+        .prop_map(|(na, pd)| na.into_iter().chain(pd).collect())
+}
+
+fn data() -> impl Strategy<Value = Vec<Vec<u8>>> {
+    prop::collection::vec(prop::collection::vec(any::<u8>(), 0..16), 0..4)
+}
+
+impl Arbitrary for DhcpOption {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            any::<Duid>().prop_map(|duid| DhcpOption::ServerId(duid.as_ref().to_vec())),
+            prop::collection::vec(any::<OptionCode>(), 0..16)
+                .prop_map(|opts| DhcpOption::ORO(ORO { opts })),
+            any::<u8>().prop_map(DhcpOption::Preference),
+            any::<u16>().prop_map(DhcpOption::ElapsedTime),
+            any::<Ipv6Addr>().prop_map(DhcpOption::ServerUnicast),
+            status_code(),
+            Just(DhcpOption::RapidCommit),
+            data().prop_map(|data| DhcpOption::UserClass(UserClass { data })),
+            (any::<u32>(), data())
+                .prop_map(|(num, data)| DhcpOption::VendorClass(VendorClass { num, data })),
+            prop::collection::vec(any::<u8>(), 1..16).prop_map(DhcpOption::InterfaceId),
+            any::<MessageType>().prop_map(DhcpOption::ReconfMsg),
+            Just(DhcpOption::ReconfAccept),
+            prop::collection::vec(any::<Ipv6Addr>(), 1..4).prop_map(DhcpOption::DomainNameServers),
+            prop::collection::vec(any::<Ipv6Addr>(), 1..4).prop_map(DhcpOption::PanaAgent),
+            any::<u32>().prop_map(DhcpOption::InformationRefreshTime),
+            (any::<u16>(), prop::collection::vec(any::<u8>(), 1..16)).prop_map(
+                |(ll_type, address)| {
+                    DhcpOption::ClientLinklayerAddr(ClientLinklayerAddr { ll_type, address })
+                }
+            ),
+            ia_addr(),
+            ia_prefix(),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for DhcpOptions {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        // a client id is always present
+        (
+            any::<Duid>(),
+            ias(),
+            prop::collection::vec(any::<DhcpOption>(), 0..8),
+        )
+            .prop_map(|(duid, ias, opts)| {
+                std::iter::once(DhcpOption::ClientId(duid.as_ref().to_vec()))
+                    .chain(ias)
+                    .chain(opts)
+                    .collect()
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Message {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (any::<MessageType>(), any::<[u8; 3]>(), any::<DhcpOptions>())
+            .prop_map(|(msg_type, xid, opts)| Message {
+                msg_type,
+                xid,
+                opts,
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for RelayMessage {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            prop_oneof![Just(MessageType::RelayForw), Just(MessageType::RelayRepl)],
+            any::<u8>(),
+            any::<Ipv6Addr>(),
+            any::<Ipv6Addr>(),
+            prop::collection::vec(any::<DhcpOption>(), 0..4),
+        )
+            .prop_map(
+                |(msg_type, hop_count, link_addr, peer_addr, opts)| RelayMessage {
+                    msg_type,
+                    hop_count,
+                    link_addr,
+                    peer_addr,
+                    opts: opts.into_iter().collect(),
+                },
+            )
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decodable, Encodable};
+
+    proptest! {
+        #[test]
+        fn roundtrip_message(msg in any::<Message>()) {
+            let buf = msg.to_vec().unwrap();
+            prop_assert_eq!(Message::from_bytes(&buf).unwrap(), msg);
+        }
+
+        #[test]
+        fn roundtrip_relay_message(msg in any::<RelayMessage>()) {
+            let buf = msg.to_vec().unwrap();
+            prop_assert_eq!(RelayMessage::from_bytes(&buf).unwrap(), msg);
+        }
+    }
+}
@@ -52,10 +52,14 @@
 //! # Ok(()) }
 //! ```
 //!
+mod addrsel;
+#[cfg(feature = "proptest")]
+mod arbitrary;
 pub mod duid;
 mod option_codes;
 mod options;
 mod oro_codes;
+mod s46;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -63,9 +67,11 @@ use serde::{Deserialize, Serialize};
 use std::{convert::TryInto, fmt, net::Ipv6Addr};
 
 // re-export submodules from v6
+pub use self::addrsel::*;
 pub use self::option_codes::*;
 pub use self::options::*;
 pub use self::oro_codes::*;
+pub use self::s46::*;
 
 pub use crate::{
     decoder::{Decodable, Decoder},
@@ -417,6 +423,17 @@ impl RelayMessage {
     pub fn opts_mut(&mut self) -> &mut DhcpOptions {
         &mut self.opts
     }
+
+    /// the client (or server) message in the relay message option (9),
+    /// looking through any nested relays. Option 9 is decoded along with
+    /// the rest of the options, so this is only a lookup.
+    pub fn encapsulated_message(&self) -> Option<&Message> {
+        match self.opts.get(OptionCode::RelayMsg)? {
+            DhcpOption::RelayMsg(RelayMessageData::Message(msg)) => Some(msg),
+            DhcpOption::RelayMsg(RelayMessageData::Relay(relay)) => relay.encapsulated_message(),
+            _ => None,
+        }
+    }
 }
 
 impl Decodable for RelayMessage {
@@ -454,6 +471,50 @@ impl fmt::Display for RelayMessage {
     }
 }
 
+/// contents of the relay message option (9), a client/server message or,
+/// with more than one relay agent, another relay message
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-21.10>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayMessageData {
+    /// client or server message
+    Message(Message),
+    /// RELAY-FORW/RELAY-REPL from another relay agent
+    Relay(RelayMessage),
+}
+
+impl Decodable for RelayMessageData {
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(match decoder.peek_u8()?.into() {
+            MessageType::RelayForw | MessageType::RelayRepl => {
+                RelayMessageData::Relay(RelayMessage::decode(decoder)?)
+            }
+            _ => RelayMessageData::Message(Message::decode(decoder)?),
+        })
+    }
+}
+
+impl Encodable for RelayMessageData {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        match self {
+            RelayMessageData::Message(msg) => msg.encode(e),
+            RelayMessageData::Relay(relay) => relay.encode(e),
+        }
+    }
+}
+
+impl From<Message> for RelayMessageData {
+    fn from(msg: Message) -> Self {
+        RelayMessageData::Message(msg)
+    }
+}
+
+impl From<RelayMessage> for RelayMessageData {
+    fn from(relay: RelayMessage) -> Self {
+        RelayMessageData::Relay(relay)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -517,6 +578,68 @@ mod tests {
         msg.set_xid_num(8);
         assert_eq!(msg.xid_num(), 8);
     }
+    // RELAY-FORW from the first relay, carrying `client`
+    fn relay_forw(client: &[u8]) -> Vec<u8> {
+        let mut buf = vec![12, 0];
+        buf.extend(Ipv6Addr::UNSPECIFIED.octets());
+        buf.extend("fe80::1".parse::<Ipv6Addr>().unwrap().octets());
+        buf.extend([0, 9]);
+        buf.extend((client.len() as u16).to_be_bytes());
+        buf.extend(client);
+        buf
+    }
+
+    #[test]
+    fn encapsulated_message() -> Result<()> {
+        // a solicit, and one shorter than the 34 byte relay header
+        let short = [1, 0x10, 0x08, 0x74, 0, 8, 0, 2, 0, 0];
+        for client in [solicit(), short.to_vec()] {
+            let buf = relay_forw(&client);
+            let relay = RelayMessage::from_bytes(&buf)?;
+            assert_eq!(
+                relay.encapsulated_message(),
+                Some(&Message::from_bytes(&client)?)
+            );
+            assert_eq!(relay.to_vec()?, buf);
+        }
+
+        // second relay, the client message is still found
+        let mut outer = RelayMessage {
+            msg_type: MessageType::RelayForw,
+            hop_count: 1,
+            link_addr: Ipv6Addr::UNSPECIFIED,
+            peer_addr: "fe80::2".parse().unwrap(),
+            opts: DhcpOptions::new(),
+        };
+        assert_eq!(outer.encapsulated_message(), None);
+        let inner = RelayMessage::from_bytes(&relay_forw(&short))?;
+        outer
+            .opts_mut()
+            .insert(DhcpOption::RelayMsg(inner.clone().into()));
+        assert_eq!(outer.encapsulated_message(), inner.encapsulated_message());
+        Ok(())
+    }
+
+    #[test]
+    fn mud_url_https() {
+        // http mud url ahead of the elapsed time
+        let mut buf = vec![1, 0x10, 0x08, 0x74, 0, 112, 0, 22];
+        buf.extend(b"http://example.org/mud");
+        buf.extend([0, 8, 0, 2, 0, 0]);
+        assert!(matches!(
+            Message::from_bytes(&buf),
+            Err(DecodeError::InvalidUrlScheme(scheme)) if scheme == "http"
+        ));
+
+        let mut msg = Message::new(MessageType::Solicit);
+        msg.opts_mut()
+            .insert(DhcpOption::MudUrlV6("http://example.org/mud".to_owned()));
+        assert!(matches!(
+            msg.to_vec(),
+            Err(EncodeError::InvalidUrlScheme(scheme)) if scheme == "http"
+        ));
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_json_v6() -> Result<()> {
@@ -140,7 +140,15 @@ pub enum OptionCode {
     FStartTimeOfState,
     FStateExpirationTime,
     RelayPort,
+    V6SztpRedirect,
+    S46BindIpv6Prefix,
+    IALL,
+    Lladdr,
+    SlapQuad,
+    V6DotsRi,
+    V6DotsAddress,
     Ipv6AddressANDSF,
+    V6Dnr,
     Unknown(u16),
 }
 
@@ -281,7 +289,15 @@ impl From<OptionCode> for u16 {
             O::FStartTimeOfState => 133,
             O::FStateExpirationTime => 134,
             O::RelayPort => 135,
+            O::V6SztpRedirect => 136,
+            O::S46BindIpv6Prefix => 137,
+            O::IALL => 138,
+            O::Lladdr => 139,
+            O::SlapQuad => 140,
+            O::V6DotsRi => 141,
+            O::V6DotsAddress => 142,
             O::Ipv6AddressANDSF => 143,
+            O::V6Dnr => 144,
             O::Unknown(n) => n,
         }
     }
@@ -424,12 +440,197 @@ impl From<u16> for OptionCode {
             133 => FStartTimeOfState,
             134 => FStateExpirationTime,
             135 => RelayPort,
+            136 => V6SztpRedirect,
+            137 => S46BindIpv6Prefix,
+            138 => IALL,
+            139 => Lladdr,
+            140 => SlapQuad,
+            141 => V6DotsRi,
+            142 => V6DotsAddress,
             143 => Ipv6AddressANDSF,
+            144 => V6Dnr,
             _ => Unknown(n),
         }
     }
 }
 
+impl OptionCode {
+    /// name of the `OptionCode` variant for a known option (i.e. `"IANA"` for 3),
+    /// not the IANA registry name (`OPTION_IA_NA`). `None` for `Unknown`
+    pub fn name(&self) -> Option<&'static str> {
+        use OptionCode as O;
+        Some(match self {
+            O::ClientId => "ClientId",
+            O::ServerId => "ServerId",
+            O::IANA => "IANA",
+            O::IATA => "IATA",
+            O::IAAddr => "IAAddr",
+            O::ORO => "ORO",
+            O::Preference => "Preference",
+            O::ElapsedTime => "ElapsedTime",
+            O::RelayMsg => "RelayMsg",
+            O::Authentication => "Authentication",
+            O::ServerUnicast => "ServerUnicast",
+            O::StatusCode => "StatusCode",
+            O::RapidCommit => "RapidCommit",
+            O::UserClass => "UserClass",
+            O::VendorClass => "VendorClass",
+            O::VendorOpts => "VendorOpts",
+            O::InterfaceId => "InterfaceId",
+            O::ReconfMsg => "ReconfMsg",
+            O::ReconfAccept => "ReconfAccept",
+            O::SipServerD => "SipServerD",
+            O::SipServerA => "SipServerA",
+            O::DomainNameServers => "DomainNameServers",
+            O::DomainSearchList => "DomainSearchList",
+            O::IAPD => "IAPD",
+            O::IAPrefix => "IAPrefix",
+            O::NisServers => "NisServers",
+            O::NispServers => "NispServers",
+            O::NisDomainName => "NisDomainName",
+            O::NispDomainName => "NispDomainName",
+            O::SntpServers => "SntpServers",
+            O::InformationRefreshTime => "InformationRefreshTime",
+            O::BcmcsServerD => "BcmcsServerD",
+            O::BcmcsServerA => "BcmcsServerA",
+            O::GeoconfCivic => "GeoconfCivic",
+            O::RemoteId => "RemoteId",
+            O::SubscriberId => "SubscriberId",
+            O::ClientFqdn => "ClientFqdn",
+            O::PanaAgent => "PanaAgent",
+            O::NewPosixTimezone => "NewPosixTimezone",
+            O::NewTzdbTimezone => "NewTzdbTimezone",
+            O::ERO => "ERO",
+            O::LqQuery => "LqQuery",
+            O::ClientData => "ClientData",
+            O::CltTime => "CltTime",
+            O::LqRelayData => "LqRelayData",
+            O::LqClientLink => "LqClientLink",
+            O::Mip6Hnidf => "Mip6Hnidf",
+            O::Mip6Vdinf => "Mip6Vdinf",
+            O::V6Lost => "V6Lost",
+            O::CapwapAcV6 => "CapwapAcV6",
+            O::RelayId => "RelayId",
+            O::Ipv6AddressMoS => "Ipv6AddressMoS",
+            O::Ipv6FQDNMoS => "Ipv6FQDNMoS",
+            O::NtpServer => "NtpServer",
+            O::V6AccessDomain => "V6AccessDomain",
+            O::SipUaCsList => "SipUaCsList",
+            O::OptBootfileUrl => "OptBootfileUrl",
+            O::OptBootfileParam => "OptBootfileParam",
+            O::ClientArchType => "ClientArchType",
+            O::Nii => "Nii",
+            O::Geolocation => "Geolocation",
+            O::AftrName => "AftrName",
+            O::ErpLocalDomainName => "ErpLocalDomainName",
+            O::Rsoo => "Rsoo",
+            O::PdExclude => "PdExclude",
+            O::Vss => "Vss",
+            O::Mip6Idinf => "Mip6Idinf",
+            O::Mip6Udinf => "Mip6Udinf",
+            O::Mip6Hnp => "Mip6Hnp",
+            O::Mip6Haa => "Mip6Haa",
+            O::Mip6Haf => "Mip6Haf",
+            O::RdnssSelection => "RdnssSelection",
+            O::KrbPrincipalName => "KrbPrincipalName",
+            O::KrbRealmName => "KrbRealmName",
+            O::KrbDefaultRealmName => "KrbDefaultRealmName",
+            O::KrbKdc => "KrbKdc",
+            O::ClientLinklayerAddr => "ClientLinklayerAddr",
+            O::LinkAddress => "LinkAddress",
+            O::Radius => "Radius",
+            O::SolMaxRt => "SolMaxRt",
+            O::InfMaxRt => "InfMaxRt",
+            O::Addrsel => "Addrsel",
+            O::AddrselTable => "AddrselTable",
+            O::V6PcpServer => "V6PcpServer",
+            O::Dhcpv4Msg => "Dhcpv4Msg",
+            O::Dhcp4ODhcp6Server => "Dhcp4ODhcp6Server",
+            O::S46Rule => "S46Rule",
+            O::S46Br => "S46Br",
+            O::S46Dmr => "S46Dmr",
+            O::S46V4v6bind => "S46V4v6bind",
+            O::S46Portparams => "S46Portparams",
+            O::S46ContMape => "S46ContMape",
+            O::S46ContMapt => "S46ContMapt",
+            O::S46ContLw => "S46ContLw",
+            O::_4Rd => "_4Rd",
+            O::_4RdMapRule => "_4RdMapRule",
+            O::_4RdNonMapRule => "_4RdNonMapRule",
+            O::LqBaseTime => "LqBaseTime",
+            O::LqStartTime => "LqStartTime",
+            O::LqEndTime => "LqEndTime",
+            O::DhcpCaptivePortal => "DhcpCaptivePortal",
+            O::MplParameters => "MplParameters",
+            O::AniAtt => "AniAtt",
+            O::AniNetworkName => "AniNetworkName",
+            O::AniApName => "AniApName",
+            O::AniApBssid => "AniApBssid",
+            O::AniOperatorId => "AniOperatorId",
+            O::AniOperatorRealm => "AniOperatorRealm",
+            O::S46Priority => "S46Priority",
+            O::MudUrlV6 => "MudUrlV6",
+            O::V6Prefix64 => "V6Prefix64",
+            O::FBindingStatus => "FBindingStatus",
+            O::FConnectFlags => "FConnectFlags",
+            O::Fdnsremovalinfo => "Fdnsremovalinfo",
+            O::FDNSHostName => "FDNSHostName",
+            O::FDNSZoneName => "FDNSZoneName",
+            O::Fdnsflags => "Fdnsflags",
+            O::Fexpirationtime => "Fexpirationtime",
+            O::FMaxUnackedBndupd => "FMaxUnackedBndupd",
+            O::FMclt => "FMclt",
+            O::FPartnerLifetime => "FPartnerLifetime",
+            O::FPartnerLifetimeSent => "FPartnerLifetimeSent",
+            O::FPartnerDownTime => "FPartnerDownTime",
+            O::FPartnerRawCltTime => "FPartnerRawCltTime",
+            O::FProtocolVersion => "FProtocolVersion",
+            O::FKeepaliveTime => "FKeepaliveTime",
+            O::FReconfigureData => "FReconfigureData",
+            O::FRelationshipName => "FRelationshipName",
+            O::FServerFlags => "FServerFlags",
+            O::FServerState => "FServerState",
+            O::FStartTimeOfState => "FStartTimeOfState",
+            O::FStateExpirationTime => "FStateExpirationTime",
+            O::RelayPort => "RelayPort",
+            O::V6SztpRedirect => "V6SztpRedirect",
+            O::S46BindIpv6Prefix => "S46BindIpv6Prefix",
+            O::IALL => "IALL",
+            O::Lladdr => "Lladdr",
+            O::SlapQuad => "SlapQuad",
+            O::V6DotsRi => "V6DotsRi",
+            O::V6DotsAddress => "V6DotsAddress",
+            O::Ipv6AddressANDSF => "Ipv6AddressANDSF",
+            O::V6Dnr => "V6Dnr",
+            O::Unknown(_) => return None,
+        })
+    }
+}
+
+/// variant name of option `code` (see [`OptionCode::name`]), `None` if it's unknown
+///
+/// ```rust
+/// use dhcproto::v6::option_code_name;
+///
+/// assert_eq!(option_code_name(3), Some("IANA"));
+/// assert_eq!(option_code_name(144), Some("V6Dnr"));
+/// assert_eq!(dhcproto::v6::OptionCode::from(1000).to_string(), "Unknown(1000)");
+/// assert_eq!(option_code_name(1000), None);
+/// ```
+pub fn option_code_name(code: u16) -> Option<&'static str> {
+    OptionCode::from(code).name()
+}
+
+// prints the variant name, i.e. "IANA", or "Unknown(code)"
+impl std::fmt::Display for OptionCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "Unknown({})", u16::from(*self)),
+        }
+    }
+}
+
 impl PartialOrd for OptionCode {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -466,10 +667,23 @@ impl From<&DhcpOption> for OptionCode {
             O::ReconfMsg(_) => OptionCode::ReconfMsg,
             O::ReconfAccept => OptionCode::ReconfAccept,
             O::DomainNameServers(_) => OptionCode::DomainNameServers,
+            O::PanaAgent(_) => OptionCode::PanaAgent,
             O::DomainSearchList(_) => OptionCode::DomainSearchList,
             O::IAPD(_) => OptionCode::IAPD,
             O::IAPrefix(_) => OptionCode::IAPrefix,
             O::InformationRefreshTime(_) => OptionCode::InformationRefreshTime,
+            O::ClientLinklayerAddr(_) => OptionCode::ClientLinklayerAddr,
+            O::MudUrlV6(_) => OptionCode::MudUrlV6,
+            O::Addrsel(_) => OptionCode::Addrsel,
+            O::Dhcpv4Msg(_) => OptionCode::Dhcpv4Msg,
+            O::S46Rule(_) => OptionCode::S46Rule,
+            O::S46Br(_) => OptionCode::S46Br,
+            O::S46Dmr(_) => OptionCode::S46Dmr,
+            O::S46V4v6bind(_) => OptionCode::S46V4v6bind,
+            O::S46Portparams(_) => OptionCode::S46Portparams,
+            O::S46ContMape(_) => OptionCode::S46ContMape,
+            O::S46ContMapt(_) => OptionCode::S46ContMapt,
+            O::S46ContLw(_) => OptionCode::S46ContLw,
             // SolMaxRt(_) => OptionCode::SolMaxRt,
             // InfMaxRt(_) => OptionCode::InfMaxRt,
             // LqQuery(_) => OptionCode::LqQuery,
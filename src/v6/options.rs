@@ -9,16 +9,22 @@ use std::{cmp::Ordering, net::Ipv6Addr, ops::RangeInclusive};
 
 use crate::v6::option_codes::OptionCode;
 use crate::{
-    decoder::{Decodable, Decoder},
-    encoder::{Encodable, Encoder},
-    error::{DecodeResult, EncodeResult},
-    v6::{MessageType, RelayMessage},
+    decoder::{check_url_scheme, Decodable, Decoder},
+    encoder::{self, Encodable, Encoder},
+    error::{DecodeError, DecodeResult, EncodeResult},
+    v4,
+    v6::{
+        AddressSelectionPolicy, MessageType, RelayMessageData, S46Dmr, S46PortParams, S46Rule,
+        S46V4v6Bind,
+    },
 };
 
 // server can send multiple IA_NA options to request multiple addresses
 // so we must be able to handle multiple of the same option type
 // <https://datatracker.ietf.org/doc/html/rfc8415#section-6.6>
 // TODO: consider HashMap<OptionCode, TinyVec<DhcpOption>>
+// inline storage (`SmallVec<[DhcpOption; N]>`) isn't possible: `DhcpOption`
+// contains `DhcpOptions` (i.e. IA_NA), so the type would be infinitely sized
 
 /// <https://datatracker.ietf.org/doc/html/rfc8415#section-21>
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -70,6 +76,15 @@ impl DhcpOptions {
         let i = self.0.partition_point(|x| x < &opt);
         self.0.insert(i, opt)
     }
+    /// insert a new option, removing any others with the same option code.
+    /// Returns the first option removed
+    pub fn insert_or_replace(&mut self, opt: DhcpOption) -> Option<DhcpOption> {
+        let old = self
+            .remove_all(OptionCode::from(&opt))
+            .and_then(|mut old| old.next());
+        self.insert(opt);
+        old
+    }
     /// return a reference to an iterator
     pub fn iter(&self) -> impl Iterator<Item = &DhcpOption> {
         self.0.iter()
@@ -120,7 +135,7 @@ pub enum DhcpOption {
     /// Elapsed time in millis
     ElapsedTime(u16),
     /// 9 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.10>
-    RelayMsg(RelayMessage),
+    RelayMsg(RelayMessageData),
     /// 11 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.11>
     Authentication(Authentication),
     /// 12 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.12>
@@ -149,7 +164,34 @@ pub enum DhcpOption {
     IAPD(IAPD),
     /// 26 - <https://datatracker.ietf.org/doc/html/rfc3633#section-10>
     IAPrefix(IAPrefix),
+    /// 40 - PANA authentication agents <https://datatracker.ietf.org/doc/html/rfc5192#section-4>
+    PanaAgent(Vec<Ipv6Addr>),
     InformationRefreshTime(u32),
+    /// 79 - <https://datatracker.ietf.org/doc/html/rfc6939>
+    ClientLinklayerAddr(ClientLinklayerAddr),
+    /// 112 - <https://datatracker.ietf.org/doc/html/rfc8520#section-10>
+    /// must be an https url
+    MudUrlV6(String),
+    /// 84 - <https://datatracker.ietf.org/doc/html/rfc7078#section-4>
+    Addrsel(AddressSelectionPolicy),
+    /// 87 - DHCPv4 message carried over DHCPv6 <https://datatracker.ietf.org/doc/html/rfc7341#section-7.1>
+    Dhcpv4Msg(Box<v4::Message>),
+    /// 89 - <https://datatracker.ietf.org/doc/html/rfc7598#section-4.1>
+    S46Rule(S46Rule),
+    /// 90 - <https://datatracker.ietf.org/doc/html/rfc7598#section-4.2>
+    S46Br(Ipv6Addr),
+    /// 91 - <https://datatracker.ietf.org/doc/html/rfc7598#section-4.3>
+    S46Dmr(S46Dmr),
+    /// 92 - <https://datatracker.ietf.org/doc/html/rfc7598#section-4.4>
+    S46V4v6bind(S46V4v6Bind),
+    /// 93 - <https://datatracker.ietf.org/doc/html/rfc7598#section-4.5>
+    S46Portparams(S46PortParams),
+    /// 94 - MAP-E container <https://datatracker.ietf.org/doc/html/rfc7598#section-5.1>
+    S46ContMape(DhcpOptions),
+    /// 95 - MAP-T container <https://datatracker.ietf.org/doc/html/rfc7598#section-5.2>
+    S46ContMapt(DhcpOptions),
+    /// 96 - Lightweight 4over6 container <https://datatracker.ietf.org/doc/html/rfc7598#section-5.3>
+    S46ContLw(DhcpOptions),
     // SolMaxRt(u32),
     // InfMaxRt(u32),
     // LqQuery(_),
@@ -163,6 +205,19 @@ pub enum DhcpOption {
     Unknown(UnknownOption),
 }
 
+impl DhcpOption {
+    /// the option's code, same as `OptionCode::from(&opt)`
+    /// ```
+    /// # use dhcproto::v6::{DhcpOption, OptionCode};
+    /// let opt = DhcpOption::Preference(255);
+    /// assert_eq!(opt.code(), OptionCode::Preference);
+    /// assert_eq!(u16::from(opt.code()), 7);
+    /// ```
+    pub fn code(&self) -> OptionCode {
+        OptionCode::from(self)
+    }
+}
+
 impl PartialOrd for DhcpOption {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -486,6 +541,27 @@ impl Decodable for IAAddr {
     }
 }
 
+/// Client link-layer address, added by the first-hop relay
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientLinklayerAddr {
+    /// hardware type from the IANA ARP parameters, i.e. 1 for ethernet
+    pub ll_type: u16,
+    pub address: Vec<u8>,
+}
+
+impl Decodable for ClientLinklayerAddr {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        let ll_type = decoder.read_u16()?;
+        let address = decoder.buffer().to_vec();
+        // the address can't be empty
+        if address.is_empty() {
+            return Err(DecodeError::NotEnoughBytes);
+        }
+        Ok(ClientLinklayerAddr { ll_type, address })
+    }
+}
+
 /// fallback for options not yet implemented
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -518,15 +594,31 @@ impl UnknownOption {
 impl Decodable for DhcpOptions {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         let mut opts = Vec::new();
-        while let Ok(opt) = DhcpOption::decode(decoder) {
-            opts.push(opt);
+        loop {
+            let start = decoder.clone();
+            match DhcpOption::decode(decoder) {
+                Ok(opt) => opts.push(opt),
+                // all of the option's bytes are here but the value is
+                // invalid, don't silently drop it & everything after it
+                Err(err) if option_complete(start.buffer()) => return Err(err),
+                // ran out of bytes or the last option is cut short, stop
+                Err(_) => break,
+            }
         }
-        // sorts by OptionCode
-        opts.sort_unstable();
+        // sorts by OptionCode, stable so repeated options (i.e. IA_NA) keep their order
+        opts.sort();
         Ok(DhcpOptions(opts))
     }
 }
 
+// true if the option at the start of `buf` has all the bytes its length declares
+fn option_complete(buf: &[u8]) -> bool {
+    match buf {
+        [_, _, l0, l1, rest @ ..] => rest.len() >= u16::from_be_bytes([*l0, *l1]) as usize,
+        _ => false,
+    }
+}
+
 impl Encodable for DhcpOptions {
     fn encode(&self, e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
         self.0.iter().try_for_each(|opt| opt.encode(e))
@@ -560,13 +652,13 @@ impl Decodable for DhcpOption {
             OptionCode::ElapsedTime => DhcpOption::ElapsedTime(decoder.read_u16()?),
             OptionCode::RelayMsg => {
                 let mut relay_dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::RelayMsg(RelayMessage::decode(&mut relay_dec)?)
+                DhcpOption::RelayMsg(RelayMessageData::decode(&mut relay_dec)?)
             }
             OptionCode::Authentication => {
                 let mut dec = Decoder::new(decoder.read_slice(len)?);
                 DhcpOption::Authentication(Authentication::decode(&mut dec)?)
             }
-            OptionCode::ServerUnicast => DhcpOption::ServerUnicast(decoder.read::<16>()?.into()),
+            OptionCode::ServerUnicast => DhcpOption::ServerUnicast(decoder.read_ipv6(len)?),
             OptionCode::StatusCode => DhcpOption::StatusCode(StatusCode {
                 status: decoder.read_u16()?.into(),
                 msg: decoder.read_string(len - std::mem::size_of::<u16>())?,
@@ -599,6 +691,7 @@ impl Decodable for DhcpOption {
             OptionCode::DomainNameServers => {
                 DhcpOption::DomainNameServers(decoder.read_ipv6s(len)?)
             }
+            OptionCode::PanaAgent => DhcpOption::PanaAgent(decoder.read_ipv6s(len)?),
             OptionCode::IAPD => {
                 let mut dec = Decoder::new(decoder.read_slice(len)?);
                 DhcpOption::IAPD(IAPD::decode(&mut dec)?)
@@ -616,6 +709,52 @@ impl Decodable for DhcpOption {
 
                 DhcpOption::DomainSearchList(names)
             }
+            OptionCode::InformationRefreshTime => {
+                DhcpOption::InformationRefreshTime(decoder.read_u32()?)
+            }
+            OptionCode::ClientLinklayerAddr => {
+                let mut dec = Decoder::new(decoder.read_slice(len)?);
+                DhcpOption::ClientLinklayerAddr(ClientLinklayerAddr::decode(&mut dec)?)
+            }
+            OptionCode::Addrsel => {
+                let mut dec = Decoder::new(decoder.read_slice(len)?);
+                DhcpOption::Addrsel(AddressSelectionPolicy::decode(&mut dec)?)
+            }
+            OptionCode::Dhcpv4Msg => {
+                let mut dec = Decoder::new(decoder.read_slice(len)?);
+                DhcpOption::Dhcpv4Msg(Box::new(v4::Message::decode(&mut dec)?))
+            }
+            OptionCode::S46Rule => {
+                let mut dec = Decoder::new(decoder.read_slice(len)?);
+                DhcpOption::S46Rule(S46Rule::decode(&mut dec)?)
+            }
+            OptionCode::S46Br => DhcpOption::S46Br(decoder.read_ipv6(len)?),
+            OptionCode::S46Dmr => {
+                let mut dec = Decoder::new(decoder.read_slice(len)?);
+                DhcpOption::S46Dmr(S46Dmr::decode(&mut dec)?)
+            }
+            OptionCode::S46V4v6bind => {
+                let mut dec = Decoder::new(decoder.read_slice(len)?);
+                DhcpOption::S46V4v6bind(S46V4v6Bind::decode(&mut dec)?)
+            }
+            OptionCode::S46Portparams => {
+                let mut dec = Decoder::new(decoder.read_slice(len)?);
+                DhcpOption::S46Portparams(S46PortParams::decode(&mut dec)?)
+            }
+            OptionCode::S46ContMape | OptionCode::S46ContMapt | OptionCode::S46ContLw => {
+                let mut dec = Decoder::new(decoder.read_slice(len)?);
+                let opts = DhcpOptions::decode(&mut dec)?;
+                match code {
+                    OptionCode::S46ContMape => DhcpOption::S46ContMape(opts),
+                    OptionCode::S46ContMapt => DhcpOption::S46ContMapt(opts),
+                    _ => DhcpOption::S46ContLw(opts),
+                }
+            }
+            OptionCode::MudUrlV6 => {
+                let url = decoder.read_string(len)?;
+                check_url_scheme(&url, &["https"])?;
+                DhcpOption::MudUrlV6(url)
+            }
             // not yet implemented
             OptionCode::Unknown(code) => DhcpOption::Unknown(UnknownOption {
                 code,
@@ -631,184 +770,146 @@ impl Decodable for DhcpOption {
 impl Encodable for DhcpOption {
     fn encode(&self, e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
         let code: OptionCode = self.into();
-        e.write_u16(code.into())?;
-        match self {
-            DhcpOption::ClientId(duid) | DhcpOption::ServerId(duid) => {
-                e.write_u16(duid.len() as u16)?;
-                e.write_slice(duid)?;
-            }
-            DhcpOption::IANA(IANA { id, t1, t2, opts })
-            | DhcpOption::IAPD(IAPD { id, t1, t2, opts }) => {
-                // write len
-                let mut buf = Vec::new();
-                let mut opt_enc = Encoder::new(&mut buf);
-                opts.encode(&mut opt_enc)?;
-                // buf now has total len
-                e.write_u16(12 + buf.len() as u16)?;
-                // write data
-                e.write_u32(*id)?;
-                e.write_u32(*t1)?;
-                e.write_u32(*t2)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::IATA(IATA { id, opts }) => {
-                // write len
-                let mut buf = Vec::new();
-                let mut opt_enc = Encoder::new(&mut buf);
-                opts.encode(&mut opt_enc)?;
-                // buf now has total len
-                e.write_u16(4 + buf.len() as u16)?;
-                // data
-                e.write_u32(*id)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::IAAddr(IAAddr {
-                addr,
-                preferred_life,
-                valid_life,
-                opts,
-            }) => {
-                // write len
-                let mut buf = Vec::new();
-                let mut opt_enc = Encoder::new(&mut buf);
-                opts.encode(&mut opt_enc)?;
-                // buf now has total len
-                e.write_u16(24 + buf.len() as u16)?;
-                // data
-                e.write_u128((*addr).into())?;
-                e.write_u32(*preferred_life)?;
-                e.write_u32(*valid_life)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::ORO(ORO { opts }) => {
-                // write len
-                e.write_u16(2 * opts.len() as u16)?;
-                // data
-                for code in opts {
-                    e.write_u16(u16::from(*code))?;
+        // length is filled in from what each arm writes
+        e.write_v6_option_with(code.into(), |e| {
+            match self {
+                DhcpOption::ClientId(duid) | DhcpOption::ServerId(duid) => {
+                    e.write_slice(duid)?;
                 }
-            }
-            DhcpOption::Preference(pref) => {
-                e.write_u16(1)?;
-                e.write_u8(*pref)?;
-            }
-            DhcpOption::ElapsedTime(elapsed) => {
-                e.write_u16(2)?;
-                e.write_u16(*elapsed)?;
-            }
-            DhcpOption::RelayMsg(msg) => {
-                let mut buf = Vec::new();
-                let mut relay_enc = Encoder::new(&mut buf);
-                msg.encode(&mut relay_enc)?;
-
-                e.write_u16(buf.len() as u16)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::Authentication(Authentication {
-                proto,
-                algo,
-                rdm,
-                replay_detection,
-                info,
-            }) => {
-                e.write_u16(11 + info.len() as u16)?;
-                e.write_u8(*proto)?;
-                e.write_u8(*algo)?;
-                e.write_u8(*rdm)?;
-                e.write_u64(*replay_detection)?;
-                e.write_slice(info)?;
-            }
-            DhcpOption::ServerUnicast(addr) => {
-                e.write_u16(16)?;
-                e.write_u128((*addr).into())?;
-            }
-            DhcpOption::StatusCode(StatusCode { status, msg }) => {
-                e.write_u16(2 + msg.len() as u16)?;
-                e.write_u16((*status).into())?;
-                e.write_slice(msg.as_bytes())?;
-            }
-            DhcpOption::RapidCommit => {
-                e.write_u16(0)?;
-            }
-            DhcpOption::UserClass(UserClass { data }) => {
-                e.write_u16(data.len() as u16)?;
-                for s in data {
-                    e.write_u16(s.len() as u16)?;
-                    e.write_slice(s)?;
+                DhcpOption::IANA(IANA { id, t1, t2, opts })
+                | DhcpOption::IAPD(IAPD { id, t1, t2, opts }) => {
+                    e.write_u32(*id)?;
+                    e.write_u32(*t1)?;
+                    e.write_u32(*t2)?;
+                    opts.encode(e)?;
                 }
-            }
-            DhcpOption::VendorClass(VendorClass { num, data }) => {
-                e.write_u16(4 + data.len() as u16)?;
-                e.write_u32(*num)?;
-                for s in data {
-                    e.write_u16(s.len() as u16)?;
-                    e.write_slice(s)?;
+                DhcpOption::IATA(IATA { id, opts }) => {
+                    e.write_u32(*id)?;
+                    opts.encode(e)?;
                 }
-            }
-            DhcpOption::VendorOpts(VendorOpts { num, opts }) => {
-                let mut buf = Vec::new();
-                let mut opt_enc = Encoder::new(&mut buf);
-                opts.encode(&mut opt_enc)?;
-                // buf now has total len
-                e.write_u16(4 + buf.len() as u16)?;
-                e.write_u32(*num)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::InterfaceId(id) => {
-                e.write_u16(id.len() as u16)?;
-                e.write_slice(id)?;
-            }
-            DhcpOption::ReconfMsg(msg_type) => {
-                e.write_u16(1)?;
-                e.write_u8((*msg_type).into())?;
-            }
-            DhcpOption::ReconfAccept => {
-                e.write_u16(0)?;
-            }
-            DhcpOption::DomainNameServers(addrs) => {
-                e.write_u16(addrs.len() as u16 * 16)?;
-                for addr in addrs {
+                DhcpOption::IAAddr(IAAddr {
+                    addr,
+                    preferred_life,
+                    valid_life,
+                    opts,
+                }) => {
                     e.write_u128((*addr).into())?;
+                    e.write_u32(*preferred_life)?;
+                    e.write_u32(*valid_life)?;
+                    opts.encode(e)?;
                 }
-            }
-            DhcpOption::DomainSearchList(names) => {
-                let mut buf = Vec::new();
-                let mut name_encoder = BinEncoder::new(&mut buf);
-                for name in names {
-                    name.emit(&mut name_encoder)?;
+                DhcpOption::ORO(ORO { opts }) => {
+                    for code in opts {
+                        e.write_u16(u16::from(*code))?;
+                    }
                 }
-                e.write_u16(buf.len() as u16)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::IAPrefix(IAPrefix {
-                preferred_lifetime,
-                valid_lifetime,
-                prefix_len,
-                prefix_ip,
-                opts,
-            }) => {
-                let mut buf = Vec::new();
-                let mut opt_enc = Encoder::new(&mut buf);
-                opts.encode(&mut opt_enc)?;
-                // buf now has total len
-                e.write_u16(25 + buf.len() as u16)?;
-                // write data
-                e.write_u32(*preferred_lifetime)?;
-                e.write_u32(*valid_lifetime)?;
-                e.write_u8(*prefix_len)?;
-                e.write_u128((*prefix_ip).into())?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::InformationRefreshTime(time) => {
-                e.write_u16(4)?;
-                e.write_u32(*time)?;
-            }
-            DhcpOption::Unknown(UnknownOption { data, .. }) => {
-                e.write_u16(data.len() as u16)?;
-                e.write_slice(data)?;
-            }
-        };
-        Ok(())
+                DhcpOption::Preference(pref) => {
+                    e.write_u8(*pref)?;
+                }
+                DhcpOption::ElapsedTime(elapsed) => {
+                    e.write_u16(*elapsed)?;
+                }
+                DhcpOption::RelayMsg(msg) => {
+                    msg.encode(e)?;
+                }
+                DhcpOption::Authentication(Authentication {
+                    proto,
+                    algo,
+                    rdm,
+                    replay_detection,
+                    info,
+                }) => {
+                    e.write_u8(*proto)?;
+                    e.write_u8(*algo)?;
+                    e.write_u8(*rdm)?;
+                    e.write_u64(*replay_detection)?;
+                    e.write_slice(info)?;
+                }
+                DhcpOption::ServerUnicast(addr) => {
+                    e.write_u128((*addr).into())?;
+                }
+                DhcpOption::StatusCode(StatusCode { status, msg }) => {
+                    e.write_u16((*status).into())?;
+                    e.write_slice(msg.as_bytes())?;
+                }
+                DhcpOption::RapidCommit | DhcpOption::ReconfAccept => {}
+                DhcpOption::UserClass(UserClass { data }) => {
+                    // each item is prefixed with a 2 byte len
+                    for s in data {
+                        e.write_u16(s.len() as u16)?;
+                        e.write_slice(s)?;
+                    }
+                }
+                DhcpOption::VendorClass(VendorClass { num, data }) => {
+                    e.write_u32(*num)?;
+                    for s in data {
+                        e.write_u16(s.len() as u16)?;
+                        e.write_slice(s)?;
+                    }
+                }
+                DhcpOption::VendorOpts(VendorOpts { num, opts }) => {
+                    e.write_u32(*num)?;
+                    opts.encode(e)?;
+                }
+                DhcpOption::InterfaceId(id) => {
+                    e.write_slice(id)?;
+                }
+                DhcpOption::ReconfMsg(msg_type) => {
+                    e.write_u8((*msg_type).into())?;
+                }
+                DhcpOption::DomainNameServers(addrs) | DhcpOption::PanaAgent(addrs) => {
+                    for addr in addrs {
+                        e.write_u128((*addr).into())?;
+                    }
+                }
+                DhcpOption::DomainSearchList(names) => {
+                    let mut buf = Vec::new();
+                    let mut name_encoder = BinEncoder::new(&mut buf);
+                    for name in names {
+                        name.emit(&mut name_encoder)?;
+                    }
+                    e.write_slice(&buf)?;
+                }
+                DhcpOption::IAPrefix(IAPrefix {
+                    preferred_lifetime,
+                    valid_lifetime,
+                    prefix_len,
+                    prefix_ip,
+                    opts,
+                }) => {
+                    e.write_u32(*preferred_lifetime)?;
+                    e.write_u32(*valid_lifetime)?;
+                    e.write_u8(*prefix_len)?;
+                    e.write_u128((*prefix_ip).into())?;
+                    opts.encode(e)?;
+                }
+                DhcpOption::InformationRefreshTime(time) => {
+                    e.write_u32(*time)?;
+                }
+                DhcpOption::ClientLinklayerAddr(ClientLinklayerAddr { ll_type, address }) => {
+                    e.write_u16(*ll_type)?;
+                    e.write_slice(address)?;
+                }
+                DhcpOption::MudUrlV6(url) => {
+                    encoder::check_url_scheme(url, &["https"])?;
+                    e.write_slice(url.as_bytes())?;
+                }
+                DhcpOption::Addrsel(policy) => policy.encode(e)?,
+                DhcpOption::Dhcpv4Msg(msg) => msg.encode(e)?,
+                DhcpOption::S46Rule(rule) => rule.encode(e)?,
+                DhcpOption::S46Br(addr) => e.write_u128((*addr).into())?,
+                DhcpOption::S46Dmr(dmr) => dmr.encode(e)?,
+                DhcpOption::S46V4v6bind(bind) => bind.encode(e)?,
+                DhcpOption::S46Portparams(params) => params.encode(e)?,
+                DhcpOption::S46ContMape(opts)
+                | DhcpOption::S46ContMapt(opts)
+                | DhcpOption::S46ContLw(opts) => opts.encode(e)?,
+                DhcpOption::Unknown(UnknownOption { data, .. }) => {
+                    e.write_slice(data)?;
+                }
+            };
+            Ok(())
+        })
     }
 }
 
@@ -916,6 +1017,67 @@ mod tests {
         assert_eq!(None, range_binsearch(&arr, |x| x.cmp(&3)));
     }
 
+    #[test]
+    fn test_client_linklayer_addr() -> DecodeResult<()> {
+        let buf = [0, 79, 0, 8, 0, 1, 0xde, 0xad, 0xbe, 0xef, 0, 1];
+        assert_eq!(
+            DhcpOption::decode(&mut Decoder::new(&buf))?,
+            DhcpOption::ClientLinklayerAddr(ClientLinklayerAddr {
+                ll_type: 1,
+                address: vec![0xde, 0xad, 0xbe, 0xef, 0, 1],
+            })
+        );
+        // type with no address
+        assert!(DhcpOption::decode(&mut Decoder::new(&[0, 79, 0, 2, 0, 1])).is_err());
+        assert!(DhcpOption::decode(&mut Decoder::new(&[0, 79, 0, 1, 0])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_or_replace() {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::Preference(1));
+        opts.insert(DhcpOption::Preference(2));
+        opts.insert(DhcpOption::ElapsedTime(0));
+        let first = opts.get(OptionCode::Preference).cloned();
+        assert_eq!(opts.insert_or_replace(DhcpOption::Preference(3)), first);
+        assert_eq!(
+            opts.get_all(OptionCode::Preference),
+            Some(&[DhcpOption::Preference(3)][..])
+        );
+        assert_eq!(opts.insert_or_replace(DhcpOption::RapidCommit), None);
+        assert_eq!(opts.iter().count(), 3);
+    }
+
+    #[test]
+    fn test_dhcpv4_msg() -> Result<(), Box<dyn std::error::Error>> {
+        let mut msg = v4::Message::default();
+        msg.set_chaddr(&[1, 2, 3, 4, 5, 6])
+            .opts_mut()
+            .insert(v4::DhcpOption::MessageType(v4::MessageType::Discover));
+        let v4_bytes = msg.to_vec()?;
+
+        let mut buf = vec![0, 87];
+        buf.extend((v4_bytes.len() as u16).to_be_bytes());
+        buf.extend(&v4_bytes);
+        let opt = DhcpOption::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(opt, DhcpOption::Dhcpv4Msg(Box::new(msg)));
+        assert_eq!(opt.to_vec()?, buf);
+        // truncated v4 header
+        assert!(DhcpOption::decode(&mut Decoder::new(&[0, 87, 0, 2, 1, 1])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mud_url_https() {
+        let mut buf = vec![0, 112, 0, 22];
+        buf.extend(b"http://example.org/mud");
+        assert!(matches!(
+            DhcpOption::decode(&mut Decoder::new(&buf)),
+            Err(DecodeError::InvalidUrlScheme(scheme)) if scheme == "http"
+        ));
+    }
+
     #[test]
     fn test_dhcpv6_opts_parsing() {
         #[rustfmt::skip]
@@ -992,4 +1154,257 @@ mod tests {
         assert_eq!(opts, expected_opts);
         assert_eq!(buffer.as_slice(), raw);
     }
+
+    #[test]
+    fn test_user_vendor_class_len() {
+        #[rustfmt::skip]
+        let raw: Vec<u8> = vec![
+            0x00, 0x0f, // OPTION_USER_CLASS(15)
+            0x00, 0x07, // length 7, not the item count
+            0x00, 0x02, b'a', b'b', // item "ab"
+            0x00, 0x01, b'c', // item "c"
+            0x00, 0x10, // OPTION_VENDOR_CLASS(16)
+            0x00, 0x0a, // length 10
+            0x00, 0x00, 0x00, 0x09, // enterprise number 9
+            0x00, 0x04, b'x', b'y', b'z', b'w', // item "xyzw"
+        ];
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::UserClass(UserClass {
+            data: vec![b"ab".to_vec(), b"c".to_vec()],
+        }));
+        opts.insert(DhcpOption::VendorClass(VendorClass {
+            num: 9,
+            data: vec![b"xyzw".to_vec()],
+        }));
+
+        let mut buffer = Vec::new();
+        opts.encode(&mut Encoder::new(&mut buffer)).unwrap();
+        assert_eq!(buffer, raw);
+        assert_eq!(DhcpOptions::decode(&mut Decoder::new(&raw)).unwrap(), opts);
+    }
+
+    // every variant should survive an encode/decode roundtrip
+    mod roundtrip {
+        use std::str::FromStr;
+
+        use super::*;
+        use crate::v6::{Message, RelayMessage};
+
+        fn ip() -> Ipv6Addr {
+            "2001:db8::1".parse().unwrap()
+        }
+
+        fn status() -> DhcpOption {
+            DhcpOption::StatusCode(StatusCode {
+                status: Status::NoAddrsAvail,
+                msg: "no addresses".to_owned(),
+            })
+        }
+
+        roundtrip_test!(client_id, DhcpOption::ClientId(vec![0, 1, 2, 3]));
+        roundtrip_test!(server_id, DhcpOption::ServerId(vec![0, 1, 2, 3]));
+        roundtrip_test!(
+            iana,
+            DhcpOption::IANA(IANA {
+                id: 1,
+                t1: 3600,
+                t2: 5400,
+                opts: vec![status()].into_iter().collect(),
+            })
+        );
+        roundtrip_test!(
+            iata,
+            DhcpOption::IATA(IATA {
+                id: 1,
+                opts: DhcpOptions::new(),
+            })
+        );
+        roundtrip_test!(
+            ia_addr,
+            DhcpOption::IAAddr(IAAddr {
+                addr: ip(),
+                preferred_life: 3600,
+                valid_life: 7200,
+                opts: DhcpOptions::new(),
+            })
+        );
+        roundtrip_test!(
+            oro,
+            DhcpOption::ORO(ORO {
+                opts: vec![OptionCode::DomainNameServers, OptionCode::DomainSearchList],
+            })
+        );
+        roundtrip_test!(preference, DhcpOption::Preference(255));
+        roundtrip_test!(elapsed_time, DhcpOption::ElapsedTime(100));
+        roundtrip_test!(
+            relay_msg,
+            DhcpOption::RelayMsg(RelayMessageData::Relay(RelayMessage {
+                msg_type: MessageType::RelayForw,
+                hop_count: 1,
+                link_addr: ip(),
+                peer_addr: Ipv6Addr::LOCALHOST,
+                opts: vec![DhcpOption::InterfaceId(vec![1, 2])]
+                    .into_iter()
+                    .collect(),
+            }))
+        );
+        roundtrip_test!(
+            relay_msg_client,
+            DhcpOption::RelayMsg(RelayMessageData::Message(Message::new_with_id(
+                MessageType::Solicit,
+                [1, 2, 3]
+            )))
+        );
+        roundtrip_test!(
+            authentication,
+            DhcpOption::Authentication(Authentication {
+                proto: 3,
+                algo: 1,
+                rdm: 0,
+                replay_detection: 42,
+                info: vec![1, 2, 3, 4],
+            })
+        );
+        roundtrip_test!(server_unicast, DhcpOption::ServerUnicast(ip()));
+        roundtrip_test!(status_code, status());
+        roundtrip_test!(rapid_commit, DhcpOption::RapidCommit);
+        roundtrip_test!(
+            user_class,
+            DhcpOption::UserClass(UserClass {
+                data: vec![b"foo".to_vec(), b"bar".to_vec()],
+            })
+        );
+        roundtrip_test!(
+            vendor_class,
+            DhcpOption::VendorClass(VendorClass {
+                num: 4491,
+                data: vec![b"docsis".to_vec()],
+            })
+        );
+        roundtrip_test!(
+            vendor_opts,
+            DhcpOption::VendorOpts(VendorOpts {
+                num: 4491,
+                opts: vec![DhcpOption::Unknown(UnknownOption::new(
+                    OptionCode::Unknown(1000),
+                    vec![1, 2, 3],
+                ))]
+                .into_iter()
+                .collect(),
+            })
+        );
+        roundtrip_test!(interface_id, DhcpOption::InterfaceId(vec![1, 2, 3]));
+        roundtrip_test!(reconf_msg, DhcpOption::ReconfMsg(MessageType::Renew));
+        roundtrip_test!(reconf_accept, DhcpOption::ReconfAccept);
+        roundtrip_test!(
+            domain_name_servers,
+            DhcpOption::DomainNameServers(vec![ip(), Ipv6Addr::LOCALHOST])
+        );
+        roundtrip_test!(pana_agent, DhcpOption::PanaAgent(vec![ip()]));
+        roundtrip_test!(
+            domain_search_list,
+            DhcpOption::DomainSearchList(vec![Name::from_str("example.org.").unwrap()])
+        );
+        roundtrip_test!(
+            iapd,
+            DhcpOption::IAPD(IAPD {
+                id: 1,
+                t1: 3600,
+                t2: 5400,
+                opts: DhcpOptions::new(),
+            })
+        );
+        roundtrip_test!(
+            ia_prefix,
+            DhcpOption::IAPrefix(IAPrefix {
+                preferred_lifetime: 3600,
+                valid_lifetime: 7200,
+                prefix_len: 56,
+                prefix_ip: ip(),
+                opts: DhcpOptions::new(),
+            })
+        );
+        roundtrip_test!(
+            information_refresh_time,
+            DhcpOption::InformationRefreshTime(86_400)
+        );
+        roundtrip_test!(
+            client_linklayer_addr,
+            DhcpOption::ClientLinklayerAddr(ClientLinklayerAddr {
+                ll_type: 1,
+                address: vec![0, 1, 2, 3, 4, 5],
+            })
+        );
+        roundtrip_test!(
+            mud_url,
+            DhcpOption::MudUrlV6("https://example.org/mud/device.json".to_owned())
+        );
+        roundtrip_test!(
+            s46_cont_mapt,
+            DhcpOption::S46ContMapt(
+                [
+                    DhcpOption::S46Rule(S46Rule {
+                        fmr: false,
+                        ea_len: 8,
+                        prefix4_len: 24,
+                        ipv4_prefix: std::net::Ipv4Addr::new(192, 0, 2, 0),
+                        prefix6_len: 56,
+                        ipv6_prefix: "2001:db8:ff00::".parse().unwrap(),
+                        opts: DhcpOptions::new(),
+                    }),
+                    DhcpOption::S46Dmr(S46Dmr {
+                        prefix6_len: 64,
+                        ipv6_prefix: "2001:db8:ffff::".parse().unwrap(),
+                    }),
+                ]
+                .into_iter()
+                .collect()
+            )
+        );
+        roundtrip_test!(
+            s46_cont_lw,
+            DhcpOption::S46ContLw(
+                [
+                    DhcpOption::S46Br(ip()),
+                    DhcpOption::S46V4v6bind(S46V4v6Bind {
+                        ipv4_addr: std::net::Ipv4Addr::new(192, 0, 2, 1),
+                        prefix6_len: 64,
+                        ipv6_prefix: "2001:db8:1::".parse().unwrap(),
+                        opts: [DhcpOption::S46Portparams(S46PortParams {
+                            offset: 0,
+                            psid_len: 6,
+                            psid: 3,
+                        })]
+                        .into_iter()
+                        .collect(),
+                    }),
+                ]
+                .into_iter()
+                .collect()
+            )
+        );
+        roundtrip_test!(
+            addrsel,
+            DhcpOption::Addrsel(AddressSelectionPolicy {
+                automatic: false,
+                privacy: true,
+                entries: vec![
+                    crate::v6::PolicyEntry {
+                        prefix: "2001:db8::/32".parse().unwrap(),
+                        precedence: 45,
+                        label: 14,
+                    },
+                    crate::v6::PolicyEntry {
+                        prefix: "::/0".parse().unwrap(),
+                        precedence: 40,
+                        label: 1,
+                    },
+                ],
+            })
+        );
+        roundtrip_test!(
+            unknown,
+            DhcpOption::Unknown(UnknownOption::new(OptionCode::Unknown(1000), vec![1, 2, 3]))
+        );
+    }
 }
@@ -72,7 +72,11 @@ pub enum OROCode {
     MplParameters,
     S46Priority,
     V6Prefix64,
+    V6SztpRedirect,
+    V6DotsRi,
+    V6DotsAddress,
     Ipv6AddressANDSF,
+    V6Dnr,
     /// Avalible for future codes.
     Unknown(u16),
 }
@@ -156,7 +160,11 @@ impl TryFrom<OptionCode> for OROCode {
             OptionCode::MplParameters => Ok(OROCode::MplParameters),
             OptionCode::S46Priority => Ok(OROCode::S46Priority),
             OptionCode::V6Prefix64 => Ok(OROCode::V6Prefix64),
+            OptionCode::V6SztpRedirect => Ok(OROCode::V6SztpRedirect),
+            OptionCode::V6DotsRi => Ok(OROCode::V6DotsRi),
+            OptionCode::V6DotsAddress => Ok(OROCode::V6DotsAddress),
             OptionCode::Ipv6AddressANDSF => Ok(OROCode::Ipv6AddressANDSF),
+            OptionCode::V6Dnr => Ok(OROCode::V6Dnr),
             OptionCode::Unknown(u16) => Ok(OROCode::Unknown(u16)),
             _ => Err("conversion error, is not a valid OROCode"),
         }
@@ -226,7 +234,11 @@ impl From<OROCode> for OptionCode {
             OROCode::MplParameters => OptionCode::MplParameters,
             OROCode::S46Priority => OptionCode::S46Priority,
             OROCode::V6Prefix64 => OptionCode::V6Prefix64,
+            OROCode::V6SztpRedirect => OptionCode::V6SztpRedirect,
+            OROCode::V6DotsRi => OptionCode::V6DotsRi,
+            OROCode::V6DotsAddress => OptionCode::V6DotsAddress,
             OROCode::Ipv6AddressANDSF => OptionCode::Ipv6AddressANDSF,
+            OROCode::V6Dnr => OptionCode::V6Dnr,
             OROCode::Unknown(u16) => OptionCode::Unknown(u16),
         }
     }
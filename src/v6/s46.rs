@@ -0,0 +1,313 @@
+//! Softwire46 (MAP-E, MAP-T & Lightweight 4over6) options
+//! <https://datatracker.ietf.org/doc/html/rfc7598>
+//!
+//! The container options ([`DhcpOption::S46ContMape`], [`DhcpOption::S46ContMapt`]
+//! & [`DhcpOption::S46ContLw`]) hold the rule, BR, DMR & binding options below.
+//!
+//! [`DhcpOption::S46ContMape`]: crate::v6::DhcpOption::S46ContMape
+//! [`DhcpOption::S46ContMapt`]: crate::v6::DhcpOption::S46ContMapt
+//! [`DhcpOption::S46ContLw`]: crate::v6::DhcpOption::S46ContLw
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    encoder::{Encodable, Encoder},
+    error::{DecodeError, DecodeResult, EncodeError, EncodeResult},
+    v6::{DhcpOptions, OptionCode},
+};
+
+// F-flag, the rule is also a forwarding mapping rule
+const FMR_FLAG: u8 = 0x01;
+
+// value limits <https://datatracker.ietf.org/doc/html/rfc7598#section-4.1>
+const MAX_EA_LEN: u8 = 48;
+const MAX_PREFIX4_LEN: u8 = 32;
+const MAX_PREFIX6_LEN: u8 = 128;
+// <https://datatracker.ietf.org/doc/html/rfc7598#section-4.5>
+const MAX_PSID_OFFSET: u8 = 15;
+const MAX_PSID_LEN: u8 = 16;
+
+/// 89 - S46 rule, a MAP basic or forwarding mapping rule. `ea_len` over 48
+/// or `prefix4_len` over 32 is a decode error
+/// <https://datatracker.ietf.org/doc/html/rfc7598#section-4.1>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S46Rule {
+    /// rule is also a forwarding mapping rule (FMR)
+    pub fmr: bool,
+    /// embedded address bits length
+    pub ea_len: u8,
+    pub prefix4_len: u8,
+    pub ipv4_prefix: Ipv4Addr,
+    pub prefix6_len: u8,
+    pub ipv6_prefix: Ipv6Addr,
+    /// rule options, i.e. [`DhcpOption::S46Portparams`](crate::v6::DhcpOption::S46Portparams)
+    pub opts: DhcpOptions,
+}
+
+impl Decodable for S46Rule {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        let flags = decoder.read_u8()?;
+        let ea_len = check_max(OptionCode::S46Rule, decoder.read_u8()?, MAX_EA_LEN)?;
+        let prefix4_len = check_max(OptionCode::S46Rule, decoder.read_u8()?, MAX_PREFIX4_LEN)?;
+        let ipv4_prefix = decoder.read::<4>()?.into();
+        let (prefix6_len, ipv6_prefix) = read_prefix6(OptionCode::S46Rule, decoder)?;
+        Ok(S46Rule {
+            fmr: flags & FMR_FLAG != 0,
+            ea_len,
+            prefix4_len,
+            ipv4_prefix,
+            prefix6_len,
+            ipv6_prefix,
+            opts: DhcpOptions::decode(decoder)?,
+        })
+    }
+}
+
+impl Encodable for S46Rule {
+    fn encode(&self, e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
+        e.write_u8(if self.fmr { FMR_FLAG } else { 0 })?;
+        e.write_u8(self.ea_len)?;
+        e.write_u8(self.prefix4_len)?;
+        e.write_ipv4(self.ipv4_prefix)?;
+        write_prefix6(OptionCode::S46Rule, self.prefix6_len, self.ipv6_prefix, e)?;
+        self.opts.encode(e)
+    }
+}
+
+/// 91 - S46 default mapping rule (MAP-T)
+/// <https://datatracker.ietf.org/doc/html/rfc7598#section-4.3>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct S46Dmr {
+    pub prefix6_len: u8,
+    pub ipv6_prefix: Ipv6Addr,
+}
+
+impl Decodable for S46Dmr {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        let (prefix6_len, ipv6_prefix) = read_prefix6(OptionCode::S46Dmr, decoder)?;
+        Ok(S46Dmr {
+            prefix6_len,
+            ipv6_prefix,
+        })
+    }
+}
+
+impl Encodable for S46Dmr {
+    fn encode(&self, e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
+        write_prefix6(OptionCode::S46Dmr, self.prefix6_len, self.ipv6_prefix, e)
+    }
+}
+
+/// 92 - S46 IPv4/IPv6 address binding (Lightweight 4over6)
+/// <https://datatracker.ietf.org/doc/html/rfc7598#section-4.4>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S46V4v6Bind {
+    pub ipv4_addr: Ipv4Addr,
+    pub prefix6_len: u8,
+    pub ipv6_prefix: Ipv6Addr,
+    /// binding options, i.e. [`DhcpOption::S46Portparams`](crate::v6::DhcpOption::S46Portparams)
+    pub opts: DhcpOptions,
+}
+
+impl Decodable for S46V4v6Bind {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        let ipv4_addr = decoder.read::<4>()?.into();
+        let (prefix6_len, ipv6_prefix) = read_prefix6(OptionCode::S46V4v6bind, decoder)?;
+        Ok(S46V4v6Bind {
+            ipv4_addr,
+            prefix6_len,
+            ipv6_prefix,
+            opts: DhcpOptions::decode(decoder)?,
+        })
+    }
+}
+
+impl Encodable for S46V4v6Bind {
+    fn encode(&self, e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
+        e.write_ipv4(self.ipv4_addr)?;
+        write_prefix6(
+            OptionCode::S46V4v6bind,
+            self.prefix6_len,
+            self.ipv6_prefix,
+            e,
+        )?;
+        self.opts.encode(e)
+    }
+}
+
+/// 93 - S46 port parameters. `offset` over 15 or `psid_len` over 16 is a
+/// decode error
+/// <https://datatracker.ietf.org/doc/html/rfc7598#section-4.5>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct S46PortParams {
+    pub offset: u8,
+    pub psid_len: u8,
+    pub psid: u16,
+}
+
+impl Decodable for S46PortParams {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        let code = OptionCode::S46Portparams;
+        Ok(S46PortParams {
+            offset: check_max(code, decoder.read_u8()?, MAX_PSID_OFFSET)?,
+            psid_len: check_max(code, decoder.read_u8()?, MAX_PSID_LEN)?,
+            psid: decoder.read_u16()?,
+        })
+    }
+}
+
+impl Encodable for S46PortParams {
+    fn encode(&self, e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
+        e.write_u8(self.offset)?;
+        e.write_u8(self.psid_len)?;
+        e.write_u16(self.psid)
+    }
+}
+
+fn check_max(code: OptionCode, value: u8, max: u8) -> DecodeResult<u8> {
+    if value > max {
+        return Err(DecodeError::ValueOutOfRange {
+            code: code.into(),
+            value: value.into(),
+        });
+    }
+    Ok(value)
+}
+
+// prefix length followed by only the bytes needed to hold `len` bits, `code`
+// is the option reported for a length over 128
+pub(super) fn read_prefix6(
+    code: OptionCode,
+    decoder: &mut Decoder<'_>,
+) -> DecodeResult<(u8, Ipv6Addr)> {
+    let len = check_max(code, decoder.read_u8()?, MAX_PREFIX6_LEN)?;
+    let mut octets = [0; 16];
+    let n = (len as usize + 7) / 8;
+    octets[..n].copy_from_slice(decoder.read_slice(n)?);
+    Ok((len, octets.into()))
+}
+
+pub(super) fn write_prefix6(
+    code: OptionCode,
+    len: u8,
+    prefix: Ipv6Addr,
+    e: &mut Encoder<'_>,
+) -> EncodeResult<()> {
+    if len > MAX_PREFIX6_LEN {
+        return Err(EncodeError::ValueOutOfRange {
+            code: code.into(),
+            value: len.into(),
+        });
+    }
+    let n = (len as usize + 7) / 8;
+    e.write_u8(len)?;
+    e.write_slice(&prefix.octets()[..n])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v6::DhcpOption;
+
+    #[test]
+    fn decode_map_e() -> DecodeResult<()> {
+        // MAP-E container with a rule (+ port params) & a BR
+        #[rustfmt::skip]
+        let buf = [
+            0, 94, 0, 45,
+            // rule
+            0, 89, 0, 21,
+            1, 16, 24, 192, 0, 2, 0, 40, 0x20, 0x01, 0x0d, 0xb8, 0x00,
+            // port params
+            0, 93, 0, 4, 6, 8, 0, 0x34,
+            // br
+            0, 90, 0, 16,
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+        ];
+        let opt = DhcpOption::decode(&mut Decoder::new(&buf))?;
+        let rule = S46Rule {
+            fmr: true,
+            ea_len: 16,
+            prefix4_len: 24,
+            ipv4_prefix: Ipv4Addr::new(192, 0, 2, 0),
+            prefix6_len: 40,
+            ipv6_prefix: "2001:db8::".parse().unwrap(),
+            opts: [DhcpOption::S46Portparams(S46PortParams {
+                offset: 6,
+                psid_len: 8,
+                psid: 0x34,
+            })]
+            .into_iter()
+            .collect(),
+        };
+        assert_eq!(
+            opt,
+            DhcpOption::S46ContMape(
+                [
+                    DhcpOption::S46Rule(rule),
+                    DhcpOption::S46Br("2001:db8::1".parse().unwrap()),
+                ]
+                .into_iter()
+                .collect()
+            )
+        );
+        assert_eq!(opt.to_vec().unwrap(), buf);
+        Ok(())
+    }
+
+    #[test]
+    fn value_limits() {
+        // ea_len 49
+        let rule = [0, 49, 24, 192, 0, 2, 0, 0];
+        assert!(matches!(
+            S46Rule::from_bytes(&rule),
+            Err(DecodeError::ValueOutOfRange {
+                code: 89,
+                value: 49
+            })
+        ));
+        // prefix4_len 33
+        assert!(S46Rule::from_bytes(&[0, 16, 33, 192, 0, 2, 0, 0]).is_err());
+        assert!(S46PortParams::from_bytes(&[15, 16, 0, 0]).is_ok());
+        assert!(matches!(
+            S46PortParams::from_bytes(&[16, 8, 0, 0]),
+            Err(DecodeError::ValueOutOfRange {
+                code: 93,
+                value: 16
+            })
+        ));
+        assert!(S46PortParams::from_bytes(&[6, 17, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn prefix6_len() {
+        // 129 bits can't fit
+        assert!(matches!(
+            S46Dmr::from_bytes(&[129; 17]),
+            Err(DecodeError::ValueOutOfRange {
+                code: 91,
+                value: 129
+            })
+        ));
+        let dmr = S46Dmr {
+            prefix6_len: 129,
+            ipv6_prefix: Ipv6Addr::UNSPECIFIED,
+        };
+        assert!(matches!(
+            dmr.to_vec(),
+            Err(EncodeError::ValueOutOfRange {
+                code: 91,
+                value: 129
+            })
+        ));
+        // 64 bits needs 8 bytes
+        assert!(S46Dmr::from_bytes(&[64, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    }
+}